@@ -1,3 +1,4 @@
+use std::path::PathBuf;
 use std::sync::Arc;
 
 use iced::futures::{SinkExt, Stream};
@@ -6,7 +7,16 @@ use iced::widget::horizontal_space;
 use iced::{Element, Subscription, Task, window};
 use smart_default::SmartDefault;
 
-use crate::fishing::{FishingArgs, FishingErr, FishingEvt, fishing_process_stream};
+use crate::catch_log;
+use crate::config::{self, Config, Profile};
+use crate::diagnostics::{self, Check};
+use crate::fishing::{FishingArgs, FishingErr, FishingEvt, SessionControl, fishing_process_stream};
+use crate::indicator::IndicatorMsg;
+use crate::ipc::{self, IpcEvents, Request, Response};
+use crate::region::{Region, RegionErr};
+use crate::state::FishingState;
+use crate::stats::SessionStats;
+use crate::timing;
 use crate::tray::{TrayEvents, TrayInput, create_icon};
 use crate::window::Window;
 
@@ -18,14 +28,71 @@ pub struct Context {
     pub err: String,
     #[default(-1)]
     pub count_down: i32,
+    pub state: Option<FishingState>,
+    pub stats: SessionStats,
+    /// Why the last session ended on its own
+    pub finish_reason: String,
 
     pub handle: Option<Arc<tokio::task::JoinHandle<()>>>,
+    pub control: Option<Arc<tokio::sync::watch::Sender<SessionControl>>>,
+    /// A stop was requested and the session is finishing its current reel
+    pub is_stopping: bool,
+    pub is_paused: bool,
 
     #[default("1")]
     pub raw_time: String,
+    pub raw_reel: String,
+    pub raw_recast: String,
+    /// Why the reel or recast text was rejected, shown under the inputs
+    pub reel_err: Option<String>,
+    pub recast_err: Option<String>,
 
     pub is_capturing: bool,
+    /// Why the last selected region was rejected, shown next to it
+    pub region_err: Option<RegionErr>,
+
+    pub profiles: Vec<Profile>,
+    pub active_profile: Option<String>,
+    pub profile_name: String,
+
     pub input_sender: Option<tokio::sync::mpsc::Sender<TrayInput>>,
+    /// Forwards session events to the control socket subscribers
+    pub events_sender: Option<tokio::sync::broadcast::Sender<ipc::Event>>,
+
+    pub setup_report: Vec<Check>,
+    pub export_status: String,
+
+    /// The text fields were edited since the config was last written
+    pub config_dirty: bool,
+    /// Cleared when the config file failed to load so it is not overwritten
+    #[default(true)]
+    pub can_save_config: bool,
+}
+
+impl Context {
+    /// Refreshes the text fields after `args` was replaced as a whole
+    fn reset_raw(&mut self) {
+        self.raw_time = self.args.time_interval.to_string();
+        self.raw_reel = self.args.reel_action.to_string();
+        self.raw_recast = self.args.recast_action.to_string();
+        self.reel_err = None;
+        self.recast_err = None;
+    }
+
+    fn status(&self) -> ipc::Status {
+        ipc::Status {
+            running: self.handle.is_some(),
+            paused: self.is_paused,
+            stopping: self.is_stopping,
+            state: self.state.map(|state| state.to_string()),
+            region: self.args.region,
+            keyword: self.args.keyword.clone(),
+            uptime_secs: self.stats.uptime().as_secs(),
+            catches: self.stats.total_matches(),
+            finish_reason: self.finish_reason.clone(),
+            error: self.err.clone(),
+        }
+    }
 }
 
 #[derive(Default)]
@@ -40,39 +107,184 @@ pub enum Message {
     WindowOpened(window::Id),
     WindowClosed(window::Id),
     Tray(TrayEvents),
+    Ipc(IpcEvents),
     GetScale,
     ScaleVal(String),
+    ScaleErr(String),
     TimeInterval(String),
     ItemName(String),
+    /// A text field was submitted, saves the edits made since the last save
+    CommitFields,
+    ReelAction(String),
+    RecastAction(String),
+    SelectProfile(String),
+    ProfileName(String),
+    SaveProfile,
+
+    CheckSetup,
+    SetupReport(Vec<Check>),
+    ExportCsv,
+    Exported(Result<PathBuf, String>),
 
     Start,
     Stop,
+    Pause,
+    Resume,
     FishingEvt(FishingEvt),
     FishingErr(Arc<FishingErr>),
 }
 
 impl Fishing {
-    pub fn new(tx: tokio::sync::mpsc::Sender<(i32, i32, i32, i32)>) -> (Self, Task<Message>) {
+    pub fn new(tx: tokio::sync::mpsc::Sender<IndicatorMsg>) -> (Self, Task<Message>) {
         let mut settings = window::Settings::default();
         settings.platform_specific.application_id = "fishing".into();
         settings.size = iced::Size::new(800.0, 600.0);
         let (_id, open) = window::open(settings);
 
+        let mut context = Context::default();
+
+        match config::load() {
+            Ok(config) => {
+                context.args = config.args;
+                context.profile_name = config.active_profile.clone().unwrap_or_default();
+                context.profiles = config.profiles;
+                context.active_profile = config.active_profile;
+            }
+            Err(e) => {
+                context.err = format!("{e}\nSettings will not be saved until it is fixed.");
+                context.can_save_config = false;
+            }
+        }
+
+        context.args.indicator_tx = Some(tx);
+        context.reset_raw();
+
         (
             Self {
-                context: Context {
-                    args: FishingArgs {
-                        indicator_tx: Some(tx),
-                        ..Default::default()
-                    },
-                    ..Default::default()
-                },
+                context,
                 ..Default::default()
             },
-            open.map(Message::WindowOpened),
+            Task::batch([
+                open.map(Message::WindowOpened),
+                Task::done(Message::CheckSetup),
+            ]),
         )
     }
 
+    /// Resets the session state and the tray icon once a session is over
+    fn end_session(&mut self) {
+        self.context.handle = None;
+        self.context.control = None;
+        self.context.is_stopping = false;
+        self.context.is_paused = false;
+        self.context.is_fishing = false;
+        self.context.count_down = -1;
+        self.context.state = None;
+        self.context.stats.stop();
+
+        let Some(tx) = &self.context.input_sender else {
+            return;
+        };
+
+        let tx = tx.clone();
+
+        tokio::spawn(async move {
+            tx.send(TrayInput::Stopped).await.unwrap_or_else(|e| {
+                println!("Cannot send: {e}");
+            });
+        });
+    }
+
+    /// Shows the active profile in the tray
+    fn sync_tray_profile(&self) {
+        let Some(tx) = &self.context.input_sender else {
+            return;
+        };
+
+        let tx = tx.clone();
+        let active = self.context.active_profile.clone();
+
+        tokio::spawn(async move {
+            tx.send(TrayInput::ActiveProfile(active))
+                .await
+                .unwrap_or_else(|e| {
+                    println!("Cannot send: {e}");
+                });
+        });
+    }
+
+    /// Writes the text field edits, which are not saved on every keystroke
+    fn commit_fields(&mut self) {
+        if self.context.config_dirty {
+            self.save_config();
+        }
+    }
+
+    fn save_config(&mut self) {
+        if !self.context.can_save_config {
+            return;
+        }
+
+        let config = Config {
+            args: self.context.args.clone(),
+            profiles: self.context.profiles.clone(),
+            active_profile: self.context.active_profile.clone(),
+        };
+
+        self.context.config_dirty = false;
+
+        if let Err(e) = config::save(&config) {
+            self.context.err = e.to_string();
+        }
+    }
+
+    fn publish(&self, event: Option<ipc::Event>) {
+        let (Some(tx), Some(event)) = (&self.context.events_sender, event) else {
+            return;
+        };
+
+        // fails only while nobody is subscribed
+        let _ = tx.send(event);
+    }
+
+    /// Overrides the given fields of the args, keeping the rest as they are
+    fn set_args(
+        &mut self,
+        fields: serde_json::Map<String, serde_json::Value>,
+    ) -> Result<(), String> {
+        let mut value = serde_json::to_value(&self.context.args).map_err(|e| e.to_string())?;
+
+        let Some(current) = value.as_object_mut() else {
+            return Err("Cannot read the current args".into());
+        };
+
+        if let Some(key) = fields.keys().find(|key| !current.contains_key(*key)) {
+            return Err(format!("Unknown field: {key}"));
+        }
+
+        current.extend(fields);
+
+        let mut args: FishingArgs = serde_json::from_value(value).map_err(|e| e.to_string())?;
+
+        if !timing::valid_secs(args.time_interval) {
+            return Err(format!("Invalid interval: {}", args.time_interval));
+        }
+
+        if let Some(timeout) = args.no_match_timeout
+            && !timing::valid_secs(timeout)
+        {
+            return Err(format!("Invalid no_match_timeout: {timeout}"));
+        }
+
+        args.indicator_tx = self.context.args.indicator_tx.take();
+
+        self.context.args = args;
+        self.context.reset_raw();
+        self.save_config();
+
+        Ok(())
+    }
+
     pub fn update(&mut self, message: Message) -> Task<Message> {
         match message {
             Message::CreateWindow => {
@@ -94,6 +306,7 @@ impl Fishing {
 
             Message::WindowClosed(_) => {
                 self.window = None;
+                self.commit_fields();
                 Task::none()
             }
 
@@ -105,7 +318,10 @@ impl Fishing {
                         Task::none()
                     }
                 }
-                TrayEvents::Quit => iced::exit(),
+                TrayEvents::Quit => {
+                    self.commit_fields();
+                    iced::exit()
+                }
                 TrayEvents::Toggle => match self.context.handle {
                     Some(_) => Task::done(Message::Stop),
                     None => Task::done(Message::Start),
@@ -114,8 +330,92 @@ impl Fishing {
                     println!("Received an error from tray: {e}");
                     Task::none()
                 }
+                TrayEvents::NextProfile => {
+                    let profiles = &self.context.profiles;
+                    let current = profiles
+                        .iter()
+                        .position(|p| Some(&p.name) == self.context.active_profile.as_ref());
+
+                    let next = match current {
+                        Some(i) => profiles.get((i + 1) % profiles.len()),
+                        None => profiles.first(),
+                    };
+
+                    match next {
+                        Some(profile) => Task::done(Message::SelectProfile(profile.name.clone())),
+                        None => Task::none(),
+                    }
+                }
                 TrayEvents::PassSender(tx) => {
                     self.context.input_sender = Some(tx);
+                    self.sync_tray_profile();
+                    Task::none()
+                }
+            },
+
+            Message::Ipc(evt) => match evt {
+                IpcEvents::Request(request, reply) => {
+                    let action_err = self
+                        .context
+                        .reel_err
+                        .clone()
+                        .or_else(|| self.context.recast_err.clone());
+
+                    let (response, task) = match request {
+                        Request::Start if self.context.handle.is_some() => (
+                            Response::Error {
+                                message: "A session is already running".into(),
+                            },
+                            Task::none(),
+                        ),
+                        Request::Start if self.context.args.region.is_none() => (
+                            Response::Error {
+                                message: RegionErr::Missing.to_string(),
+                            },
+                            Task::none(),
+                        ),
+                        // Message::Start refuses these too, without telling anyone
+                        Request::Start if action_err.is_some() => (
+                            Response::Error {
+                                message: format!(
+                                    "Invalid action: {}",
+                                    action_err.unwrap_or_default()
+                                ),
+                            },
+                            Task::none(),
+                        ),
+                        Request::Start => (Response::Ok, Task::done(Message::Start)),
+
+                        Request::Stop if self.context.handle.is_none() => (
+                            Response::Error {
+                                message: "No session is running".into(),
+                            },
+                            Task::none(),
+                        ),
+                        Request::Stop => (Response::Ok, Task::done(Message::Stop)),
+
+                        Request::SetArgs { args } => match self.set_args(args) {
+                            Ok(()) => (Response::Ok, Task::none()),
+                            Err(message) => (Response::Error { message }, Task::none()),
+                        },
+
+                        Request::GetStatus => {
+                            (Response::Status(self.context.status()), Task::none())
+                        }
+
+                        // answered by the socket itself
+                        Request::Subscribe => (Response::Ok, Task::none()),
+                    };
+
+                    reply.send(response);
+                    task
+                }
+                IpcEvents::PassSender(tx) => {
+                    self.context.events_sender = Some(tx);
+                    Task::none()
+                }
+                IpcEvents::Err(e) => {
+                    println!("Control socket failed: {e}");
                     Task::none()
                 }
             },
@@ -127,19 +427,128 @@ impl Fishing {
             }
 
             Message::TimeInterval(str) => {
-                let Ok(num) = str.parse::<f32>() else {
+                let Ok(num) = timing::parse_secs(&str) else {
                     self.context.raw_time = str;
                     return Task::none();
                 };
 
                 self.context.args.time_interval = num;
                 self.context.raw_time = str;
+                self.context.config_dirty = true;
 
                 Task::none()
             }
 
+            Message::ReelAction(str) => {
+                match str.parse() {
+                    Ok(action) => {
+                        self.context.args.reel_action = action;
+                        self.context.reel_err = None;
+                        self.context.config_dirty = true;
+                    }
+                    Err(e) => self.context.reel_err = Some(e),
+                }
+
+                self.context.raw_reel = str;
+                Task::none()
+            }
+
+            Message::RecastAction(str) => {
+                match str.parse() {
+                    Ok(action) => {
+                        self.context.args.recast_action = action;
+                        self.context.recast_err = None;
+                        self.context.config_dirty = true;
+                    }
+                    Err(e) => self.context.recast_err = Some(e),
+                }
+
+                self.context.raw_recast = str;
+                Task::none()
+            }
+
             Message::ItemName(name) => {
                 self.context.args.keyword = name;
+                self.context.config_dirty = true;
+                Task::none()
+            }
+
+            Message::CommitFields => {
+                self.commit_fields();
+                Task::none()
+            }
+
+            Message::SelectProfile(name) => {
+                let Some(profile) = self.context.profiles.iter().find(|p| p.name == name) else {
+                    self.context.err = format!("Unknown profile: {name}");
+                    return Task::none();
+                };
+
+                let indicator_tx = self.context.args.indicator_tx.take();
+                self.context.args = FishingArgs {
+                    indicator_tx,
+                    ..profile.args.clone()
+                };
+                self.context.reset_raw();
+                self.context.region_err = None;
+                self.context.profile_name = name.clone();
+                self.context.active_profile = Some(name);
+                self.save_config();
+                self.sync_tray_profile();
+
+                Task::none()
+            }
+
+            Message::ProfileName(name) => {
+                self.context.profile_name = name;
+                Task::none()
+            }
+
+            Message::SaveProfile => {
+                let name = self.context.profile_name.trim().to_string();
+
+                if name.is_empty() {
+                    self.context.err = "Profile name cannot be empty".into();
+                    return Task::none();
+                }
+
+                let args = FishingArgs {
+                    indicator_tx: None,
+                    ..self.context.args.clone()
+                };
+
+                match self.context.profiles.iter_mut().find(|p| p.name == name) {
+                    Some(profile) => profile.args = args,
+                    None => self.context.profiles.push(Profile {
+                        name: name.clone(),
+                        args,
+                    }),
+                }
+
+                self.context.active_profile = Some(name);
+                self.save_config();
+                self.sync_tray_profile();
+
+                Task::none()
+            }
+
+            Message::CheckSetup => Task::perform(
+                diagnostics::run_checks(self.context.args.input.clone()),
+                Message::SetupReport,
+            ),
+
+            Message::SetupReport(report) => {
+                self.context.setup_report = report;
+                Task::none()
+            }
+
+            Message::ExportCsv => Task::perform(catch_log::export_csv(), Message::Exported),
+
+            Message::Exported(res) => {
+                self.context.export_status = match res {
+                    Ok(path) => format!("Exported catches to {}", path.display()),
+                    Err(e) => e,
+                };
                 Task::none()
             }
 
@@ -148,9 +557,22 @@ impl Fishing {
                     return Task::none();
                 }
 
+                if self.context.args.region.is_none() {
+                    self.context.region_err = Some(RegionErr::Missing);
+                    return Task::none();
+                }
+
+                // the last valid action would be used, not the one shown
+                if self.context.reel_err.is_some() || self.context.recast_err.is_some() {
+                    return Task::none();
+                }
+
                 self.context.count_down = 3;
+                self.context.stats = SessionStats::start();
+                self.context.finish_reason = "".into();
                 self.context.err = "".into();
                 self.context.is_fishing = true;
+                self.commit_fields();
 
                 let Some(tx) = &self.context.input_sender else {
                     return Task::none();
@@ -172,48 +594,121 @@ impl Fishing {
                     return Task::none();
                 };
 
-                handle.abort();
-                self.context.handle = None;
-                self.context.is_fishing = false;
-                self.context.count_down = -1;
+                // a second Stop while the reel finishes aborts right away
+                match &self.context.control {
+                    Some(control) if !self.context.is_stopping => {
+                        control.send_replace(SessionControl::Stop);
+                        self.context.is_stopping = true;
+                    }
+                    _ => {
+                        handle.abort();
+
+                        // the aborted task cannot hide the indicator itself
+                        if let Some(indicator_tx) = &self.context.args.indicator_tx {
+                            indicator_tx
+                                .try_send(IndicatorMsg::Hide)
+                                .unwrap_or_else(|e| {
+                                    println!("Cannot send indicator: {e}");
+                                });
+                        }
+
+                        self.end_session();
+                    }
+                }
 
-                let Some(tx) = &self.context.input_sender else {
+                Task::none()
+            }
+
+            Message::Pause | Message::Resume => {
+                let Some(control) = &self.context.control else {
                     return Task::none();
                 };
 
-                let tx = tx.clone();
+                if self.context.is_stopping {
+                    return Task::none();
+                }
 
-                tokio::spawn(async move {
-                    tx.send(TrayInput::Stopped).await.unwrap_or_else(|e| {
-                        println!("Cannot send: {e}");
-                    });
+                let paused = matches!(message, Message::Pause);
+                control.send_replace(if paused {
+                    SessionControl::Pause
+                } else {
+                    SessionControl::Run
                 });
+                self.context.is_paused = paused;
 
                 Task::none()
             }
 
-            Message::ScaleVal(str) => {
-                self.context.args.scale = str;
+            Message::ScaleErr(e) => {
+                self.context.err = e;
                 self.context.is_capturing = false;
                 Task::none()
             }
 
-            Message::FishingEvt(evt) => match evt {
-                FishingEvt::PassHandle(handle) => {
-                    self.context.handle = Some(handle);
-                    Task::none()
-                }
+            Message::ScaleVal(str) => {
+                self.context.is_capturing = false;
 
-                FishingEvt::CountDown(num) => {
-                    self.context.count_down = num;
-                    Task::none()
+                match str.parse::<Region>() {
+                    Ok(region) => {
+                        self.context.args.region = Some(region);
+                        self.context.region_err = None;
+                        self.save_config();
+                    }
+                    Err(e) => self.context.region_err = Some(e),
                 }
 
-                FishingEvt::Err(e) => Task::done(Message::FishingErr(e)),
-            },
+                Task::none()
+            }
+
+            Message::FishingEvt(evt) => {
+                self.publish(ipc::Event::from_evt(&evt));
+
+                match evt {
+                    FishingEvt::PassHandle(handle) => {
+                        self.context.handle = Some(handle);
+                        Task::none()
+                    }
+
+                    FishingEvt::PassControl(control) => {
+                        self.context.control = Some(control);
+                        Task::none()
+                    }
+
+                    FishingEvt::CountDown(num) => {
+                        self.context.count_down = num;
+                        Task::none()
+                    }
+
+                    FishingEvt::State(state) => {
+                        self.context.state = Some(state);
+                        Task::none()
+                    }
+
+                    FishingEvt::TimeoutRecast
+                    | FishingEvt::FrameCaptured
+                    | FishingEvt::OcrDone
+                    | FishingEvt::Matched(_)
+                    | FishingEvt::ClickSent(_) => {
+                        self.context.stats.apply(&evt);
+                        Task::none()
+                    }
+
+                    FishingEvt::Finished(reason) => {
+                        self.context.finish_reason = format!("Session ended: {reason}");
+                        self.end_session();
+                        Task::none()
+                    }
+
+                    FishingEvt::Err(e) => Task::done(Message::FishingErr(e)),
+                }
+            }
 
             Message::FishingErr(err) => {
+                self.publish(Some(ipc::Event::Error(err.to_string())));
                 self.context.err = err.to_string();
+
+                // the session task has already exited, the error text stays visible
+                self.end_session();
                 Task::none()
             }
         }
@@ -233,6 +728,9 @@ impl Fishing {
             iced::Subscription::run(|| tray_events())
                 .map(|val| val.map_or_else(|e| TrayEvents::Err(e), |e| e))
                 .map(Message::Tray),
+            iced::Subscription::run(ipc_events)
+                .map(|val| val.unwrap_or_else(IpcEvents::Err))
+                .map(Message::Ipc),
             scale_capture(self.context.is_capturing),
             fishing_process(self.context.is_fishing, &self.context.args),
         ])
@@ -250,13 +748,19 @@ fn tray_events() -> impl Stream<Item = Result<TrayEvents, String>> {
     })
 }
 
+fn ipc_events() -> impl Stream<Item = Result<IpcEvents, String>> {
+    try_channel(1, move |output| async move {
+        ipc::serve(output).await.map_err(|e| e.to_string())?;
+        Ok(())
+    })
+}
+
 fn scale_capture(is_capturing: bool) -> Subscription<Message> {
     if !is_capturing {
         return Subscription::none();
     }
 
-    Subscription::run(scale_capture_stream)
-        .map(|val| val.map_or_else(|e| Message::ScaleVal(e), |v| v))
+    Subscription::run(scale_capture_stream).map(|val| val.unwrap_or_else(Message::ScaleErr))
 }
 
 fn scale_capture_stream() -> impl Stream<Item = Result<Message, String>> {
@@ -265,7 +769,9 @@ fn scale_capture_stream() -> impl Stream<Item = Result<Message, String>> {
             Ok(out) => out,
             Err(e) => {
                 println!("Cannot get scale: {e}");
-                return Err(e.to_string());
+                return Err(format!(
+                    "Cannot run slurp: {e}. Use \"Check setup\" for details"
+                ));
             }
         };
 
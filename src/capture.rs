@@ -0,0 +1,120 @@
+use std::path::PathBuf;
+
+use iced::futures::future::BoxFuture;
+use image::DynamicImage;
+use serde::{Deserialize, Serialize};
+use smart_default::SmartDefault;
+
+use crate::{fishing::FishingErr, region::Region};
+
+/// A source of frames for the fishing loop.
+pub trait CaptureBackend: Send {
+    /// Captures the given region and returns the decoded frame.
+    fn capture<'a>(
+        &'a mut self,
+        region: &'a Region,
+    ) -> BoxFuture<'a, Result<DynamicImage, FishingErr>>;
+}
+
+#[derive(Debug, Clone, SmartDefault, Serialize, Deserialize)]
+pub enum CaptureKind {
+    #[default]
+    Grim,
+    /// Reads every frame from an existing image, ignoring the region.
+    File(PathBuf),
+}
+
+impl CaptureKind {
+    pub fn create(&self) -> Result<Box<dyn CaptureBackend>, FishingErr> {
+        match self {
+            CaptureKind::Grim => Ok(Box::new(GrimCapture)),
+            CaptureKind::File(path) => Ok(Box::new(FileCapture { path: path.clone() })),
+        }
+    }
+}
+
+/// Runs grim with its output on stdout so frames never touch the disk.
+pub struct GrimCapture;
+
+impl CaptureBackend for GrimCapture {
+    fn capture<'a>(
+        &'a mut self,
+        region: &'a Region,
+    ) -> BoxFuture<'a, Result<DynamicImage, FishingErr>> {
+        Box::pin(async move {
+            let out = tokio::process::Command::new("grim")
+                .arg("-g")
+                .arg(region.to_string())
+                .arg("-")
+                .output()
+                .await?;
+
+            if !out.status.success() {
+                return Err(FishingErr::String(format!(
+                    "grim failed: {}",
+                    String::from_utf8_lossy(&out.stderr).trim()
+                )));
+            }
+
+            println!("grim done");
+
+            Ok(image::load_from_memory(&out.stdout)?)
+        })
+    }
+}
+
+pub struct FileCapture {
+    path: PathBuf,
+}
+
+impl CaptureBackend for FileCapture {
+    fn capture<'a>(
+        &'a mut self,
+        _region: &'a Region,
+    ) -> BoxFuture<'a, Result<DynamicImage, FishingErr>> {
+        Box::pin(async move {
+            let bytes = tokio::fs::read(&self.path).await?;
+            Ok(image::load_from_memory(&bytes)?)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const REGION: Region = Region {
+        x: 0,
+        y: 0,
+        w: 1,
+        h: 1,
+    };
+
+    #[tokio::test]
+    async fn file_capture_returns_the_image() {
+        let path =
+            std::env::temp_dir().join(format!("auto_fishing_frame_{}.png", std::process::id()));
+        image::RgbaImage::from_pixel(4, 3, image::Rgba([255, 0, 0, 255]))
+            .save(&path)
+            .unwrap();
+
+        let mut capture = CaptureKind::File(path.clone()).create().unwrap();
+        let frame = capture.capture(&REGION).await;
+        std::fs::remove_file(&path).unwrap();
+
+        let frame = frame.unwrap().into_rgba8();
+        assert_eq!(frame.dimensions(), (4, 3));
+        assert_eq!(frame.get_pixel(2, 1), &image::Rgba([255, 0, 0, 255]));
+    }
+
+    #[tokio::test]
+    async fn file_capture_reports_a_missing_file() {
+        let path = std::env::temp_dir().join("auto_fishing_missing_frame.png");
+        let mut capture = CaptureKind::File(path).create().unwrap();
+
+        assert!(matches!(
+            capture.capture(&REGION).await,
+            Err(FishingErr::IoErr(_))
+        ));
+    }
+}
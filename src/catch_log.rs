@@ -0,0 +1,126 @@
+use std::{
+    path::PathBuf,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use serde::{Deserialize, Serialize};
+use tokio::io::AsyncWriteExt;
+
+/// One detected catch, stored as a line of `catches.jsonl`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CatchRecord {
+    /// Seconds since the unix epoch
+    pub timestamp: u64,
+    pub keyword: String,
+    pub text: String,
+    pub session_id: String,
+}
+
+/// Appends the catches of one session to the shared log.
+pub struct CatchLog {
+    path: PathBuf,
+    session_id: String,
+}
+
+pub fn data_dir() -> PathBuf {
+    let mut path = match std::env::var("XDG_DATA_HOME") {
+        Ok(dir) if !dir.is_empty() => PathBuf::from(dir),
+        _ => {
+            let home = std::env::var("HOME").expect("No home");
+            PathBuf::from(home).join(".local/share")
+        }
+    };
+
+    path.push("auto_fishing");
+    path
+}
+
+pub fn log_path() -> PathBuf {
+    data_dir().join("catches.jsonl")
+}
+
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or_default()
+}
+
+impl CatchLog {
+    pub fn new_session() -> Self {
+        let millis = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_millis())
+            .unwrap_or_default();
+
+        Self {
+            path: log_path(),
+            session_id: format!("{millis:x}-{}", std::process::id()),
+        }
+    }
+
+    pub async fn append(&self, keyword: &str, text: &str) -> std::io::Result<()> {
+        let record = CatchRecord {
+            timestamp: now(),
+            keyword: keyword.to_string(),
+            text: text.trim().to_string(),
+            session_id: self.session_id.clone(),
+        };
+
+        let mut line = serde_json::to_string(&record)?;
+        line.push('\n');
+
+        if let Some(dir) = self.path.parent() {
+            tokio::fs::create_dir_all(dir).await?;
+        }
+
+        tokio::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)
+            .await?
+            .write_all(line.as_bytes())
+            .await
+    }
+}
+
+/// Converts the whole catch log to `catches.csv` next to it, returning its path.
+pub async fn export_csv() -> Result<PathBuf, String> {
+    let raw = tokio::fs::read_to_string(log_path())
+        .await
+        .map_err(|e| format!("Cannot read {}: {e}", log_path().display()))?;
+
+    let mut csv = String::from("timestamp,keyword,text,session_id\n");
+
+    for (i, line) in raw
+        .lines()
+        .enumerate()
+        .filter(|(_, l)| !l.trim().is_empty())
+    {
+        let record: CatchRecord = serde_json::from_str(line)
+            .map_err(|e| format!("Malformed catch on line {}: {e}", i + 1))?;
+
+        csv.push_str(&format!(
+            "{},{},{},{}\n",
+            record.timestamp,
+            csv_field(&record.keyword),
+            csv_field(&record.text),
+            csv_field(&record.session_id)
+        ));
+    }
+
+    let path = data_dir().join("catches.csv");
+    tokio::fs::write(&path, csv)
+        .await
+        .map_err(|e| format!("Cannot write {}: {e}", path.display()))?;
+
+    Ok(path)
+}
+
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
@@ -0,0 +1,363 @@
+use iced::futures::StreamExt;
+use thiserror::Error;
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+
+use crate::capture::CaptureKind;
+use crate::fishing::{FishingArgs, SessionControl, start_fishing};
+use crate::input::ActionSpec;
+use crate::ipc::{self, Request, Response};
+use crate::region::{Region, RegionErr};
+use crate::timing;
+
+const USAGE: &str = "Usage: auto_fishing [--headless [OPTIONS]]
+       auto_fishing ctl <start|stop|status|set [SET OPTIONS]>
+
+Options:
+    --headless              Run a fishing session without the window and tray
+    --region <GEOMETRY>     Capture region in slurp format, e.g. \"10,20 300x40\"
+    --interval <SECONDS>    Delay between two polls
+    --keywords <LIST>       Comma separated catch keywords
+    --capture-file <PATH>   Read frames from an image instead of grim
+    -h, --help              Print this message
+
+Commands:
+    ctl start               Start a session in the running instance
+    ctl stop                Stop its session, a second stop aborts right away
+    ctl status              Print the state of its session
+    ctl set                 Change its args for the next session
+
+Set options:
+    --keyword <LIST>        Comma separated catch keywords
+    --interval <SECONDS>    Delay between two polls
+    --region <GEOMETRY>     Capture region in slurp format
+    --reel <ACTION>         Reel action, e.g. left@20
+    --recast <ACTION>       Recast action, e.g. key:30@120x2";
+
+pub enum Command {
+    Gui,
+    Headless(Box<FishingArgs>),
+    /// Sends one request to an already running instance
+    Ctl(Request),
+    Help,
+}
+
+#[derive(Debug, Error)]
+pub enum CliErr {
+    #[error("Missing value for {0}")]
+    MissingValue(String),
+    #[error("Invalid value for {0}: {1}")]
+    InvalidValue(String, String),
+    #[error("Unknown argument: {0}\n\n{USAGE}")]
+    Unknown(String),
+    #[error("{0} requires --headless")]
+    NotHeadless(String),
+    #[error("Missing ctl command\n\n{USAGE}")]
+    MissingCtl,
+    #[error("ctl set needs at least one option")]
+    EmptySet,
+}
+
+pub fn parse_args(mut args: impl Iterator<Item = String>) -> Result<Command, CliErr> {
+    let mut headless = false;
+    let mut fishing_args = FishingArgs::default();
+    let mut first_option = None;
+
+    while let Some(arg) = args.next() {
+        let mut value = || args.next().ok_or_else(|| CliErr::MissingValue(arg.clone()));
+
+        match arg.as_str() {
+            "--headless" => {
+                headless = true;
+                continue;
+            }
+            "-h" | "--help" => return Ok(Command::Help),
+            "ctl" if !headless && first_option.is_none() => {
+                return parse_ctl(&mut args).map(Command::Ctl);
+            }
+            "--region" => {
+                let raw = value()?;
+                let region = raw
+                    .parse()
+                    .map_err(|e: RegionErr| CliErr::InvalidValue(arg.clone(), e.to_string()))?;
+                fishing_args.region = Some(region);
+            }
+            "--interval" => {
+                let raw = value()?;
+                fishing_args.time_interval =
+                    timing::parse_secs(&raw).map_err(|e| CliErr::InvalidValue(arg.clone(), e))?;
+            }
+            "--keywords" => fishing_args.keyword = value()?,
+            "--capture-file" => fishing_args.capture = CaptureKind::File(value()?.into()),
+            _ => return Err(CliErr::Unknown(arg)),
+        }
+
+        first_option.get_or_insert(arg);
+    }
+
+    match (headless, first_option) {
+        (true, _) => Ok(Command::Headless(Box::new(fishing_args))),
+        (false, Some(option)) => Err(CliErr::NotHeadless(option)),
+        (false, None) => Ok(Command::Gui),
+    }
+}
+
+fn parse_ctl(args: &mut impl Iterator<Item = String>) -> Result<Request, CliErr> {
+    let command = args.next().ok_or(CliErr::MissingCtl)?;
+
+    let request = match command.as_str() {
+        "start" => Request::Start,
+        "stop" => Request::Stop,
+        "status" => Request::GetStatus,
+        "set" => parse_set(args)?,
+        _ => return Err(CliErr::Unknown(command)),
+    };
+
+    // only `set` takes options
+    match args.next() {
+        Some(arg) => Err(CliErr::Unknown(arg)),
+        None => Ok(request),
+    }
+}
+
+/// Turns the set options into `FishingArgs` fields, checked here so typos fail early
+fn parse_set(args: &mut impl Iterator<Item = String>) -> Result<Request, CliErr> {
+    let mut fields = serde_json::Map::new();
+
+    while let Some(arg) = args.next() {
+        let raw = args
+            .next()
+            .ok_or_else(|| CliErr::MissingValue(arg.clone()))?;
+        let invalid = |e: String| CliErr::InvalidValue(arg.clone(), e);
+
+        let (field, value) = match arg.as_str() {
+            "--keyword" | "--keywords" => ("keyword", raw.into()),
+            "--interval" => {
+                let interval = timing::parse_secs(&raw).map_err(invalid)?;
+                ("time_interval", interval.into())
+            }
+            "--region" => {
+                let region: Region = raw.parse().map_err(|e: RegionErr| invalid(e.to_string()))?;
+                ("region", region.to_string().into())
+            }
+            "--reel" | "--recast" => {
+                let action: ActionSpec = raw.parse().map_err(invalid)?;
+                let field = if arg == "--reel" {
+                    "reel_action"
+                } else {
+                    "recast_action"
+                };
+                (field, action.to_string().into())
+            }
+            _ => return Err(CliErr::Unknown(arg)),
+        };
+
+        fields.insert(field.into(), value);
+    }
+
+    if fields.is_empty() {
+        return Err(CliErr::EmptySet);
+    }
+
+    Ok(Request::SetArgs { args: fields })
+}
+
+pub fn print_usage() {
+    println!("{USAGE}");
+}
+
+pub fn run_headless(args: Box<FishingArgs>) -> Result<(), Box<dyn std::error::Error>> {
+    let rt = tokio::runtime::Builder::new_multi_thread()
+        .enable_all()
+        .build()?;
+
+    rt.block_on(async move {
+        let (tx, mut rx) = iced::futures::channel::mpsc::channel(1);
+        let (control_tx, control_rx) = tokio::sync::watch::channel(SessionControl::Run);
+        let session = tokio::spawn(start_fishing(*args, control_rx, tx));
+
+        // the first Ctrl-C lets the current reel finish, the second one exits right away
+        tokio::spawn(async move {
+            while tokio::signal::ctrl_c().await.is_ok() {
+                if *control_tx.borrow() == SessionControl::Stop {
+                    std::process::exit(130);
+                }
+
+                println!("Stopping after the current cycle, press Ctrl-C again to exit now");
+                control_tx.send_replace(SessionControl::Stop);
+            }
+        });
+
+        while let Some(evt) = rx.next().await {
+            println!("{evt:?}");
+        }
+
+        let reason = session.await??;
+        println!("Session finished: {reason}");
+
+        Ok(())
+    })
+}
+
+pub fn run_ctl(request: Request) -> Result<(), Box<dyn std::error::Error>> {
+    let rt = tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()?;
+
+    rt.block_on(async move {
+        let path = ipc::socket_path()?;
+
+        let stream = match tokio::net::UnixStream::connect(&path).await {
+            Ok(stream) => stream,
+            Err(e)
+                if matches!(
+                    e.kind(),
+                    std::io::ErrorKind::NotFound | std::io::ErrorKind::ConnectionRefused
+                ) =>
+            {
+                return Err(format!(
+                    "auto_fishing is not running, no socket at {}",
+                    path.display()
+                )
+                .into());
+            }
+            Err(e) => return Err(e.into()),
+        };
+
+        let (reader, mut writer) = stream.into_split();
+
+        let mut line = serde_json::to_string(&request)?;
+        line.push('\n');
+        writer.write_all(line.as_bytes()).await?;
+
+        let Some(line) = BufReader::new(reader).lines().next_line().await? else {
+            return Err("auto_fishing closed the connection without answering".into());
+        };
+
+        match serde_json::from_str(&line)? {
+            Response::Ok => Ok(()),
+            Response::Status(status) => {
+                println!("Running: {}", status.running);
+                println!("Paused: {}", status.paused);
+                println!("Stopping: {}", status.stopping);
+                println!("State: {}", status.state.unwrap_or_else(|| "-".into()));
+                println!(
+                    "Region: {}",
+                    status
+                        .region
+                        .map(|region| region.to_string())
+                        .unwrap_or_else(|| "-".into())
+                );
+                println!("Keyword: {}", status.keyword);
+                println!("Uptime: {}s", status.uptime_secs);
+                println!("Catches: {}", status.catches);
+
+                if !status.finish_reason.is_empty() {
+                    println!("{}", status.finish_reason);
+                }
+
+                if !status.error.is_empty() {
+                    println!("Error: {}", status.error);
+                }
+
+                Ok(())
+            }
+            Response::Event(event) => Err(format!("Unexpected event: {event:?}").into()),
+            Response::Error { message } => Err(message.into()),
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(args: &[&str]) -> Result<Command, CliErr> {
+        parse_args(args.iter().map(|arg| arg.to_string()))
+    }
+
+    fn set_fields(args: &[&str]) -> serde_json::Map<String, serde_json::Value> {
+        match parse(args) {
+            Ok(Command::Ctl(Request::SetArgs { args })) => args,
+            _ => panic!("{args:?} should parse into ctl set"),
+        }
+    }
+
+    #[test]
+    fn ctl_only_as_the_first_argument() {
+        assert!(matches!(
+            parse(&["ctl", "start"]),
+            Ok(Command::Ctl(Request::Start))
+        ));
+        assert!(matches!(
+            parse(&["ctl", "status"]),
+            Ok(Command::Ctl(Request::GetStatus))
+        ));
+        assert!(matches!(parse(&["ctl"]), Err(CliErr::MissingCtl)));
+
+        assert!(matches!(
+            parse(&["--headless", "ctl", "start"]),
+            Err(CliErr::Unknown(arg)) if arg == "ctl"
+        ));
+        assert!(matches!(
+            parse(&["--keywords", "Ebonkoi", "ctl", "start"]),
+            Err(CliErr::Unknown(arg)) if arg == "ctl"
+        ));
+    }
+
+    #[test]
+    fn options_require_headless() {
+        assert!(matches!(parse(&[]), Ok(Command::Gui)));
+        assert!(matches!(
+            parse(&["--interval", "0.5"]),
+            Err(CliErr::NotHeadless(option)) if option == "--interval"
+        ));
+
+        let Ok(Command::Headless(args)) = parse(&["--interval", "0.5", "--headless"]) else {
+            panic!("--headless after the options should still run headless");
+        };
+        assert_eq!(args.time_interval, 0.5);
+    }
+
+    #[test]
+    fn ctl_rejects_extra_arguments() {
+        assert!(matches!(
+            parse(&["ctl", "start", "now"]),
+            Err(CliErr::Unknown(arg)) if arg == "now"
+        ));
+        assert!(matches!(
+            parse(&["ctl", "stop", "--keyword", "Ebonkoi"]),
+            Err(CliErr::Unknown(arg)) if arg == "--keyword"
+        ));
+        assert!(matches!(
+            parse(&["ctl", "restart"]),
+            Err(CliErr::Unknown(arg)) if arg == "restart"
+        ));
+    }
+
+    #[test]
+    fn ctl_set_keyword_aliases() {
+        for flag in ["--keyword", "--keywords"] {
+            let fields = set_fields(&["ctl", "set", flag, "Ebonkoi,Golden Carp"]);
+
+            assert_eq!(fields.len(), 1);
+            assert_eq!(fields["keyword"], "Ebonkoi,Golden Carp");
+        }
+    }
+
+    #[test]
+    fn ctl_set_checks_its_values() {
+        let fields = set_fields(&["ctl", "set", "--interval", "2", "--reel", "left@20"]);
+        assert_eq!(fields["time_interval"], 2.0);
+        assert_eq!(fields["reel_action"], "left@20");
+
+        assert!(matches!(parse(&["ctl", "set"]), Err(CliErr::EmptySet)));
+        assert!(matches!(
+            parse(&["ctl", "set", "--interval"]),
+            Err(CliErr::MissingValue(arg)) if arg == "--interval"
+        ));
+        assert!(matches!(
+            parse(&["ctl", "set", "--interval", "-1"]),
+            Err(CliErr::InvalidValue(arg, _)) if arg == "--interval"
+        ));
+    }
+}
@@ -0,0 +1,68 @@
+use std::path::PathBuf;
+
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::fishing::FishingArgs;
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Config {
+    pub args: FishingArgs,
+    pub profiles: Vec<Profile>,
+    pub active_profile: Option<String>,
+}
+
+/// A named set of fishing settings, e.g. one per biome or target fish.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Profile {
+    pub name: String,
+    pub args: FishingArgs,
+}
+
+#[derive(Debug, Error)]
+pub enum ConfigErr {
+    #[error("Cannot access config {0}: {1}")]
+    IoErr(PathBuf, std::io::Error),
+    #[error("Malformed config {0}: {1}")]
+    ParseErr(PathBuf, toml::de::Error),
+    #[error("Cannot serialize config: {0}")]
+    SerializeErr(#[from] toml::ser::Error),
+}
+
+pub fn config_path() -> PathBuf {
+    let mut path = match std::env::var("XDG_CONFIG_HOME") {
+        Ok(dir) if !dir.is_empty() => PathBuf::from(dir),
+        _ => {
+            let home = std::env::var("HOME").expect("No home");
+            PathBuf::from(home).join(".config")
+        }
+    };
+
+    path.push("auto_fishing/config.toml");
+    path
+}
+
+/// Loads the config file, returning the defaults if it does not exist yet.
+pub fn load() -> Result<Config, ConfigErr> {
+    let path = config_path();
+
+    let raw = match std::fs::read_to_string(&path) {
+        Ok(raw) => raw,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Config::default()),
+        Err(e) => return Err(ConfigErr::IoErr(path, e)),
+    };
+
+    toml::from_str(&raw).map_err(|e| ConfigErr::ParseErr(path, e))
+}
+
+pub fn save(config: &Config) -> Result<(), ConfigErr> {
+    let path = config_path();
+    let raw = toml::to_string_pretty(config)?;
+
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir).map_err(|e| ConfigErr::IoErr(dir.to_path_buf(), e))?;
+    }
+
+    std::fs::write(&path, raw).map_err(|e| ConfigErr::IoErr(path, e))
+}
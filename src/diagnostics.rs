@@ -0,0 +1,168 @@
+use std::path::{Path, PathBuf};
+
+use crate::input::InputKind;
+
+/// The outcome of probing one external dependency.
+#[derive(Debug, Clone)]
+pub struct Check {
+    pub name: &'static str,
+    pub ok: bool,
+    pub detail: String,
+    pub hint: String,
+}
+
+const TOOLS: [(&str, &str); 3] = [
+    (
+        "grim",
+        "Install grim from your package manager, it captures the region",
+    ),
+    (
+        "slurp",
+        "Install slurp from your package manager, it selects the region",
+    ),
+    (
+        "ydotool",
+        "Install ydotool, only needed for the ydotool input backend",
+    ),
+];
+
+/// Probes every external tool, the input backend in use and the `eng` traineddata.
+pub async fn run_checks(input: InputKind) -> Vec<Check> {
+    let mut checks: Vec<Check> = TOOLS
+        .iter()
+        .map(|&(name, hint)| {
+            // a backend that is not selected cannot break a session
+            let needed = name != "ydotool" || matches!(input, InputKind::Ydotool);
+
+            match find_in_path(name) {
+                Some(path) => Check {
+                    name,
+                    ok: true,
+                    detail: format!("found at {}", path.display()),
+                    hint: hint.into(),
+                },
+                None => Check {
+                    name,
+                    ok: !needed,
+                    detail: if needed {
+                        "not found in PATH".into()
+                    } else {
+                        "not found in PATH, not used by the selected input".into()
+                    },
+                    hint: hint.into(),
+                },
+            }
+        })
+        .collect();
+
+    // only probed when selected, the tool alone cannot inject without the daemon
+    if matches!(input, InputKind::Ydotool) {
+        checks.push(check_ydotoold());
+    }
+
+    #[cfg(target_os = "linux")]
+    checks.push(check_uinput(matches!(input, InputKind::Uinput)));
+
+    let tessdata = tokio::task::spawn_blocking(|| tesseract::Tesseract::new(None, Some("eng")))
+        .await
+        .map_err(|e| e.to_string())
+        .and_then(|res| res.map(|_| ()).map_err(|e| e.to_string()));
+
+    checks.push(Check {
+        name: "tessdata (eng)",
+        ok: tessdata.is_ok(),
+        detail: match tessdata {
+            Ok(()) => "loaded".into(),
+            Err(e) => e,
+        },
+        hint: "Install the English traineddata (tesseract-data-eng or tesseract-ocr-eng) \
+            or point TESSDATA_PREFIX at it"
+            .into(),
+    });
+
+    checks
+}
+
+/// Opens `/dev/uinput` for writing the way the uinput backend does
+#[cfg(target_os = "linux")]
+fn check_uinput(needed: bool) -> Check {
+    let err = match std::fs::OpenOptions::new().write(true).open("/dev/uinput") {
+        Ok(_) => {
+            return Check {
+                name: "/dev/uinput",
+                ok: true,
+                detail: "writable".into(),
+                hint: String::new(),
+            };
+        }
+        Err(e) => e,
+    };
+
+    let hint = match err.kind() {
+        std::io::ErrorKind::PermissionDenied => crate::input::permission_hint(),
+        std::io::ErrorKind::NotFound => "Load the module with `sudo modprobe uinput`".into(),
+        _ => "Only needed for the uinput input backend".into(),
+    };
+
+    Check {
+        name: "/dev/uinput",
+        ok: !needed,
+        detail: if needed {
+            err.to_string()
+        } else {
+            format!("{err}, not used by the selected input")
+        },
+        hint,
+    }
+}
+
+/// Looks for the socket `ydotool` talks to, `$YDOTOOL_SOCKET` or its default
+fn check_ydotoold() -> Check {
+    use std::os::unix::fs::FileTypeExt;
+
+    let path = std::env::var_os("YDOTOOL_SOCKET")
+        .filter(|path| !path.is_empty())
+        .map_or_else(|| PathBuf::from("/tmp/.ydotool_socket"), PathBuf::from);
+
+    let (ok, detail, hint) = match std::fs::metadata(&path) {
+        Ok(meta) if meta.file_type().is_socket() => (true, "socket found".into(), String::new()),
+        Ok(_) => (
+            false,
+            "exists but is not a socket".into(),
+            "Remove it and restart ydotoold, or point YDOTOOL_SOCKET at its socket".into(),
+        ),
+        Err(e) if e.kind() != std::io::ErrorKind::NotFound => (
+            false,
+            e.to_string(),
+            "Run ydotoold as your user or make its socket reachable".into(),
+        ),
+        Err(e) => (
+            false,
+            e.to_string(),
+            "Start ydotoold, e.g. `systemctl --user start ydotool`, or point YDOTOOL_SOCKET at its socket"
+                .into(),
+        ),
+    };
+
+    Check {
+        name: "ydotoold",
+        ok,
+        detail: format!("{}: {detail}", path.display()),
+        hint,
+    }
+}
+
+fn find_in_path(name: &str) -> Option<PathBuf> {
+    let paths = std::env::var_os("PATH")?;
+
+    std::env::split_paths(&paths)
+        .map(|dir| dir.join(name))
+        .find(|path| is_executable(path))
+}
+
+fn is_executable(path: &Path) -> bool {
+    use std::os::unix::fs::PermissionsExt;
+
+    path.metadata()
+        .is_ok_and(|meta| meta.is_file() && meta.permissions().mode() & 0o111 != 0)
+}
@@ -1,30 +1,56 @@
-use std::{convert::Infallible, path::PathBuf, sync::Arc};
+use std::sync::Arc;
 
 use iced::{
     futures::{SinkExt, Stream},
     stream::try_channel,
 };
+use serde::{Deserialize, Serialize};
 use smart_default::SmartDefault;
-use tesseract::{InitializeError, SetImageError, plumbing::TessBaseApiGetUtf8TextError};
+use tesseract::{
+    InitializeError,
+    plumbing::{TessBaseApiGetUtf8TextError, TessBaseApiSetImageSafetyError},
+};
 use thiserror::Error;
-use tokio::task::JoinHandle;
+use tokio::{sync::watch, task::JoinHandle};
+
+use crate::{
+    capture::CaptureKind,
+    catch_log::CatchLog,
+    indicator::IndicatorMsg,
+    input::{self, ActionSpec, InputKind},
+    matcher::{Matcher, RuleErr},
+    ocr::OcrEngine,
+    region::{Region, RegionErr},
+    state::{FishingState, StateTimeouts},
+    stop::{StopConditions, StopReason, StopTracker},
+    timing::{self, Humanizer, Timing},
+};
 
 pub fn fishing_process_stream(
     args: FishingArgs,
 ) -> impl Stream<Item = Result<FishingEvt, Arc<FishingErr>>> {
     try_channel(1, move |mut tx| async move {
         let tx_clone = tx.clone();
+        let (control_tx, control_rx) = watch::channel(SessionControl::Run);
 
         let handle = tokio::spawn(async move {
             let mut tx_clone0 = tx_clone.clone();
-            let Err(e) = start_fishing(args, tx_clone).await;
-
-            tx_clone0
-                .send(FishingEvt::Err(Arc::new(e)))
-                .await
-                .unwrap_or_else(|e| {
-                    println!("Canot send error: {e}");
-                });
+            let indicator_tx = args.indicator_tx.clone();
+
+            let evt = match start_fishing(args, control_rx, tx_clone).await {
+                Ok(reason) => {
+                    send_indicator(&indicator_tx, IndicatorMsg::Hide);
+                    FishingEvt::Finished(reason)
+                }
+                Err(e) => {
+                    send_indicator(&indicator_tx, IndicatorMsg::Error(e.to_string()));
+                    FishingEvt::Err(Arc::new(e))
+                }
+            };
+
+            tx_clone0.send(evt).await.unwrap_or_else(|e| {
+                println!("Canot send error: {e}");
+            });
         });
 
         tx.send(FishingEvt::PassHandle(Arc::new(handle)))
@@ -33,14 +59,41 @@ pub fn fishing_process_stream(
                 println!("Cannot send handle: {e}");
             });
 
+        tx.send(FishingEvt::PassControl(Arc::new(control_tx)))
+            .await
+            .unwrap_or_else(|e| {
+                println!("Cannot send control: {e}");
+            });
+
         Ok(())
     })
 }
 
+/// Requests from the app to a running session, honoured between cycles.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SessionControl {
+    Run,
+    Pause,
+    /// Finish the current reel, then end the session
+    Stop,
+}
+
 #[derive(Debug, Clone)]
 pub enum FishingEvt {
     PassHandle(Arc<JoinHandle<()>>),
+    PassControl(Arc<watch::Sender<SessionControl>>),
     CountDown(i32),
+    State(FishingState),
+    /// Nothing matched within the no-match timeout, so the line is reeled in and recast
+    TimeoutRecast,
+    FrameCaptured,
+    OcrDone,
+    /// The source of the rule that matched
+    Matched(String),
+    /// Number of presses sent by one action
+    ClickSent(u32),
+    /// A stop condition or a stop request ended the session
+    Finished(StopReason),
     Err(Arc<FishingErr>),
 }
 
@@ -50,53 +103,98 @@ pub enum FishingErr {
     IoErr(#[from] std::io::Error),
     #[error("OCR Init Error: {0}")]
     InitErr(#[from] InitializeError),
-    #[error("OCR Imagee Error: {0}")]
-    ImgErr(#[from] SetImageError),
+    #[error("OCR Frame Error: {0}")]
+    FrameErr(#[from] TessBaseApiSetImageSafetyError),
+    #[error("Capture Error: {0}")]
+    CaptureErr(#[from] image::ImageError),
     #[error("OCR Error: {0}")]
     OCRErr(#[from] TessBaseApiGetUtf8TextError),
+    #[error("Input Error: {0}")]
+    InputErr(String),
+    #[error("Region Error: {0}")]
+    RegionErr(#[from] RegionErr),
+    #[error("Keyword Rule Error: {0}")]
+    RuleErr(#[from] RuleErr),
     #[error("String: {0}")]
     String(String),
 }
 
-async fn click() {
-    let res = tokio::process::Command::new("ydotool")
-        .arg("click")
-        .arg("0xC0")
-        .output()
-        .await;
-
-    if let Err(e) = res {
-        println!("Cannot click: {e}");
-    }
-}
-
-#[derive(Debug, Clone, SmartDefault)]
+#[derive(Debug, Clone, SmartDefault, Serialize, Deserialize)]
+#[serde(default)]
 pub struct FishingArgs {
-    #[default("--")]
-    pub scale: String,
+    pub region: Option<Region>,
     #[default(1.0)]
     pub time_interval: f32,
     #[default("Ebonkoi")]
     pub keyword: String,
-    pub indicator_tx: Option<tokio::sync::mpsc::Sender<(i32, i32, i32, i32)>>,
+    /// Edit distance allowed for keywords without their own `~N` suffix, see [`Matcher`]
+    #[default(0)]
+    pub fuzzy_distance: usize,
+    pub capture: CaptureKind,
+    pub input: InputKind,
+    pub reel_action: ActionSpec,
+    pub recast_action: ActionSpec,
+    /// Cast the line when the countdown ends instead of expecting it in the water
+    pub cast_on_start: bool,
+    pub timeouts: StateTimeouts,
+    pub timing: Timing,
+    /// Seconds without a keyword hit before reeling in and recasting
+    pub no_match_timeout: Option<f32>,
+    pub stop: StopConditions,
+    #[serde(skip)]
+    pub indicator_tx: Option<tokio::sync::mpsc::Sender<IndicatorMsg>>,
 }
 
 pub async fn start_fishing(
     FishingArgs {
-        scale,
+        region,
         time_interval,
         keyword,
+        fuzzy_distance,
+        capture,
+        input,
+        reel_action,
+        recast_action,
+        cast_on_start,
+        timeouts,
+        timing,
+        no_match_timeout,
+        stop,
         indicator_tx,
     }: FishingArgs,
+    mut control: watch::Receiver<SessionControl>,
     mut tx: iced::futures::channel::mpsc::Sender<FishingEvt>,
-) -> Result<Infallible, FishingErr> {
-    let home = std::env::var("HOME").expect("No home");
-    let mut path = PathBuf::from(home);
-    path.push(".cache/auto_fishing/");
+) -> Result<StopReason, FishingErr> {
+    let region = region.ok_or(RegionErr::Missing)?;
+
+    // a config edited by hand may still hold one
+    if !timing::valid_secs(time_interval) {
+        return Err(FishingErr::String(format!(
+            "Invalid interval: {time_interval}"
+        )));
+    }
+
+    if let Some(timeout) = no_match_timeout
+        && !timing::valid_secs(timeout)
+    {
+        return Err(FishingErr::String(format!(
+            "Invalid no_match_timeout: {timeout}"
+        )));
+    }
 
-    let _ = tokio::fs::create_dir_all(&path).await;
+    timing.validate().map_err(FishingErr::String)?;
 
-    path.push("screenshot.png");
+    let matcher = Matcher::parse(&keyword, fuzzy_distance)?;
+    let mut capture = capture.create()?;
+    let mut input = input.create()?;
+    let mut humanizer = Humanizer::new(timing);
+    let catch_log = CatchLog::new_session();
+    let mut stop = StopTracker::new(stop, fuzzy_distance)?;
+    // set by a catch, acted on once the line is reeled in
+    let mut pending_stop = None;
+    let mut ocr = OcrEngine::new()?;
+
+    send_evt(&mut tx, FishingEvt::State(FishingState::Idle)).await;
 
     tokio::time::sleep(tokio::time::Duration::from_secs(1)).await;
     tx.send(FishingEvt::CountDown(2)).await.unwrap_or_else(|e| {
@@ -113,86 +211,190 @@ pub async fn start_fishing(
         println!("Cannot send fishing event: {e}");
     });
 
-    // let (x, y, w, h) = parse_coordinates(&scale).map_err(|e| FishingErr::String(e.to_string()))?;
-    // indicator_tx
-    //     .clone()
-    //     .unwrap()
-    //     .send((w, h, x, y))
-    //     .await
-    //     .unwrap_or_else(|e| {
-    //         println!("Cannot send indicator: {e}");
-    //     });
+    send_indicator(&indicator_tx, IndicatorMsg::Show(region));
+
+    let mut state = if cast_on_start {
+        FishingState::Casting
+    } else {
+        FishingState::Waiting
+    };
+
+    send_evt(&mut tx, FishingEvt::State(state)).await;
+    send_indicator(&indicator_tx, IndicatorMsg::State(state));
 
-    let keywords: Vec<&str> = keyword.split(",").collect();
+    let mut waiting_since = tokio::time::Instant::now();
 
     loop {
-        tokio::process::Command::new("grim")
-            .arg("-g")
-            .arg(&format!("{}", &scale))
-            .arg(&path)
-            .output()
-            .await?;
+        let next = match state {
+            FishingState::Idle => FishingState::Waiting,
+
+            FishingState::Casting => {
+                input::perform(input.as_mut(), &recast_action).await?;
+                send_evt(&mut tx, FishingEvt::ClickSent(recast_action.repeat)).await;
+                sleep_secs(timeouts.casting).await;
+                FishingState::Waiting
+            }
 
-        println!("grim done");
+            FishingState::Waiting if requested(&control) == SessionControl::Stop => {
+                return Ok(StopReason::Stopped);
+            }
 
-        println!("Spawned indicator");
+            FishingState::Waiting if requested(&control) == SessionControl::Pause => {
+                FishingState::Paused
+            }
+
+            FishingState::Waiting => {
+                if let Some(reason) = stop.check_duration() {
+                    return Ok(reason);
+                }
+
+                let frame = capture.capture(&region).await?.into_rgba8();
+                send_evt(&mut tx, FishingEvt::FrameCaptured).await;
+
+                let text = ocr.recognize(&frame)?;
+                send_evt(&mut tx, FishingEvt::OcrDone).await;
+
+                println!("OCR: {}", text);
+                send_indicator(&indicator_tx, IndicatorMsg::Text(text.clone()));
+
+                let evaluation = matcher.evaluate(&text);
+
+                if let Some(best) = &evaluation.best {
+                    println!(
+                        "Best match: {} (distance {}, allowed {})",
+                        best.rule.source,
+                        best.distance,
+                        best.rule.max_distance()
+                    );
+                }
+
+                if let Some(rule) = evaluation.excluded_by {
+                    println!("Excluded by: {}", rule.source);
+                }
+
+                let timed_out = no_match_timeout
+                    .is_some_and(|timeout| waiting_since.elapsed().as_secs_f32() >= timeout);
+
+                if let Some(best) = evaluation.best.as_ref().filter(|_| evaluation.is_hit()) {
+                    if let Err(e) = catch_log.append(&best.rule.source, &text).await {
+                        println!("Cannot write catch log: {e}");
+                    }
+
+                    send_evt(&mut tx, FishingEvt::Matched(best.rule.source.clone())).await;
+                    send_indicator(&indicator_tx, IndicatorMsg::Catch);
+                    pending_stop = stop.record_catch(&best.rule.source, &text);
+
+                    FishingState::Hooked
+                } else if timed_out {
+                    println!("No match within the timeout, recasting");
+                    send_evt(&mut tx, FishingEvt::TimeoutRecast).await;
+                    FishingState::Reeling
+                } else {
+                    sleep_or_control(time_interval, &mut control).await;
+                    println!("slept");
+                    continue;
+                }
+            }
+
+            FishingState::Hooked => {
+                sleep_secs(humanizer.reel_delay()).await;
+                FishingState::Reeling
+            }
 
-        let ocr = tesseract::Tesseract::new(None, Some("eng"))?;
-        let mut ocr = ocr.set_image(&path.to_str().expect("No image"))?;
-        let text = ocr.get_text()?;
+            FishingState::Reeling => {
+                input::perform(input.as_mut(), &reel_action).await?;
+                send_evt(&mut tx, FishingEvt::ClickSent(reel_action.repeat)).await;
 
-        println!("OCR: {}", text);
+                if let Some(reason) = pending_stop.take() {
+                    return Ok(reason);
+                }
 
-        let mut contains = false;
+                if requested(&control) == SessionControl::Stop {
+                    return Ok(StopReason::Stopped);
+                }
 
-        for kwd in keywords.iter() {
-            if text.contains(kwd) {
-                contains = true;
-                break;
+                println!("click");
+                sleep_secs(humanizer.recast_delay()).await;
+                FishingState::Recasting
             }
-        }
 
-        if contains {
-            click().await;
+            FishingState::Paused => {
+                // a closed channel reads as a stop request, so this cannot spin
+                let _ = control.changed().await;
 
-            tokio::time::sleep(tokio::time::Duration::from_secs_f64(1.0)).await;
-            println!("click");
+                match requested(&control) {
+                    SessionControl::Run => FishingState::Waiting,
+                    SessionControl::Pause => continue,
+                    SessionControl::Stop => return Ok(StopReason::Stopped),
+                }
+            }
 
-            click().await;
+            FishingState::Recasting => {
+                input::perform(input.as_mut(), &recast_action).await?;
+                send_evt(&mut tx, FishingEvt::ClickSent(recast_action.repeat)).await;
+                println!("YOOO");
+                sleep_secs(timeouts.recasting).await;
+                FishingState::Waiting
+            }
+        };
 
-            println!("YOOO");
-        }
+        println!("State: {state} -> {next}");
 
-        tokio::time::sleep(tokio::time::Duration::from_secs_f32(time_interval)).await;
+        if next == FishingState::Waiting {
+            waiting_since = tokio::time::Instant::now();
+        }
 
-        println!("slept");
+        state = next;
+        send_evt(&mut tx, FishingEvt::State(state)).await;
+        send_indicator(&indicator_tx, IndicatorMsg::State(state));
     }
 }
 
-fn parse_coordinates(input: &str) -> Result<(i32, i32, i32, i32), Box<dyn std::error::Error>> {
-    // Split by space to separate the two parts
-    let parts: Vec<&str> = input.split_whitespace().collect();
-    if parts.len() != 2 {
-        return Err("Input should have two parts separated by space".into());
-    }
+async fn sleep_secs(secs: f32) {
+    // from_secs_f32 panics on negative and non-finite values from the config
+    let duration = tokio::time::Duration::try_from_secs_f32(secs).unwrap_or_else(|e| {
+        println!("Invalid delay {secs}: {e}");
+        tokio::time::Duration::ZERO
+    });
 
-    // Parse the first part which is separated by comma
-    let first_parts: Vec<&str> = parts[0].split(',').collect();
-    if first_parts.len() != 2 {
-        return Err("First part should contain two numbers separated by comma".into());
+    tokio::time::sleep(duration).await;
+}
+
+/// Sleeps, waking early when the app pauses or stops the session
+async fn sleep_or_control(secs: f32, control: &mut watch::Receiver<SessionControl>) {
+    tokio::select! {
+        _ = sleep_secs(secs) => {}
+        _ = control.changed() => {}
     }
+}
 
-    // Parse the second part which is separated by 'x'
-    let second_parts: Vec<&str> = parts[1].split('x').collect();
-    if second_parts.len() != 2 {
-        return Err("Second part should contain two numbers separated by 'x'".into());
+/// The latest request from the app, treating a dropped sender as a stop
+fn requested(control: &watch::Receiver<SessionControl>) -> SessionControl {
+    match control.has_changed() {
+        Ok(_) => *control.borrow(),
+        Err(_) => SessionControl::Stop,
     }
+}
 
-    // Parse all four values as i32
-    let a = first_parts[0].parse::<i32>()?;
-    let b = first_parts[1].parse::<i32>()?;
-    let c = second_parts[0].parse::<i32>()?;
-    let d = second_parts[1].parse::<i32>()?;
+async fn send_evt(tx: &mut iced::futures::channel::mpsc::Sender<FishingEvt>, evt: FishingEvt) {
+    tx.send(evt).await.unwrap_or_else(|e| {
+        println!("Cannot send fishing event: {e}");
+    });
+}
 
-    Ok((a, b, c, d))
+/// Updates the overlay without waiting on it, a stalled gtk thread must not stall fishing
+fn send_indicator(
+    indicator_tx: &Option<tokio::sync::mpsc::Sender<IndicatorMsg>>,
+    msg: IndicatorMsg,
+) {
+    let Some(indicator_tx) = indicator_tx else {
+        return;
+    };
+
+    match indicator_tx.try_send(msg) {
+        Ok(()) => {}
+        // the overlay fell behind, the next update replaces this one anyway
+        Err(tokio::sync::mpsc::error::TrySendError::Full(_)) => {}
+        Err(e) => println!("Cannot send indicator: {e}"),
+    }
 }
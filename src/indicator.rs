@@ -1,20 +1,66 @@
-use std::rc::Rc;
+use std::{cell::RefCell, rc::Rc};
 
 use gtk4::{
     Application, ApplicationWindow, CssProvider,
     gdk::{
         Display,
-        prelude::{DisplayExt, MonitorExt},
+        prelude::{DisplayExt, MonitorExt, SurfaceExt},
     },
     gio::prelude::{ApplicationExt, ApplicationExtManual},
     glib::object::Cast,
-    prelude::{GtkApplicationExt, GtkWindowExt, WidgetExt},
+    prelude::{BoxExt, GtkApplicationExt, GtkWindowExt, NativeExt, WidgetExt},
 };
 use gtk4_layer_shell::LayerShell;
 
-fn init_context() -> Context {
-    let mut context = Context::default();
+use crate::{region::Region, state::FishingState};
 
+/// Width of the outline drawn around the capture region, kept outside of it so
+/// that grim never captures the outline itself
+const BORDER: i32 = 2;
+
+/// How long the outline flashes after a catch
+const FLASH_MS: u64 = 400;
+
+/// How long a failed session stays outlined before the overlay hides
+const ERROR_LINGER_SECS: u64 = 3;
+
+/// The outline classes that tell the session state apart, see style.css
+const STATE_CLASSES: [&str; 4] = ["waiting", "matched", "paused", "error"];
+
+#[derive(Debug, Clone)]
+pub enum IndicatorMsg {
+    Show(Region),
+    State(FishingState),
+    /// The latest OCR output
+    Text(String),
+    Catch,
+    Error(String),
+    Hide,
+}
+
+/// The widgets built on activation, updated from the indicator channel
+#[derive(Clone)]
+struct Overlay {
+    window: ApplicationWindow,
+    outline: gtk4::Box,
+    label: gtk4::Label,
+}
+
+impl Overlay {
+    fn set_state_class(&self, class: &str) {
+        for class in STATE_CLASSES {
+            self.outline.remove_css_class(class);
+        }
+
+        self.outline.add_css_class(class);
+    }
+}
+
+/// Finds the monitor showing the region, by its center or else by the largest overlap.
+///
+/// slurp and gdk both report logical coordinates in the compositor layout, so the
+/// region is compared as is and the scale factor only matters for the log line.
+fn find_monitor(region: &Region) -> Option<gtk4::gdk::Monitor> {
     // Get the display
     let display = gtk4::gdk::Display::default().expect("Could not get default display");
 
@@ -29,33 +75,47 @@ fn init_context() -> Context {
         }
     }
 
-    if monitor_list.is_empty() {
-        panic!("No monitor found");
-    }
+    let region_rect = gtk4::gdk::Rectangle::new(region.x, region.y, region.w, region.h);
+    let (center_x, center_y) = (
+        region.x.saturating_add(region.w / 2),
+        region.y.saturating_add(region.h / 2),
+    );
 
-    let monitor = monitor_list[0].clone();
-    let geometry = monitor.geometry();
-    context.offset_x = geometry.x();
-    context.offset_y = geometry.y();
+    let monitor = monitor_list
+        .iter()
+        .find(|monitor| monitor.geometry().contains_point(center_x, center_y))
+        .or_else(|| {
+            monitor_list.iter().max_by_key(|monitor| {
+                monitor
+                    .geometry()
+                    .intersect(&region_rect)
+                    .map_or(0, |overlap| overlap.width() * overlap.height())
+            })
+        })?
+        .clone();
 
-    context
-}
+    let geometry = monitor.geometry();
+    println!(
+        "Indicator on {} at {},{} {}x{} (scale {})",
+        monitor.connector().unwrap_or_default(),
+        geometry.x(),
+        geometry.y(),
+        geometry.width(),
+        geometry.height(),
+        monitor.scale_factor()
+    );
 
-#[derive(Default, Clone)]
-struct Context {
-    offset_x: i32,
-    offset_y: i32,
+    Some(monitor)
 }
 
-pub fn start_gtk(
-    mut rx: tokio::sync::mpsc::Receiver<(i32, i32, i32, i32)>,
-) -> gtk4::glib::ExitCode {
+pub fn start_gtk(mut rx: tokio::sync::mpsc::Receiver<IndicatorMsg>) -> gtk4::glib::ExitCode {
     gtk4::init().expect("Cannot initialize gtk");
-    let context = init_context();
 
     let app = Rc::new(
         Application::builder()
-            .application_id("com.example.gtk-layer-shell-demo")
+            .application_id("dev.auto_fishing.Indicator")
+            // a second instance would otherwise hand off to the first one and return at once
+            .flags(gtk4::gio::ApplicationFlags::NON_UNIQUE)
             .build(),
     );
 
@@ -70,38 +130,106 @@ pub fn start_gtk(
         gtk4::STYLE_PROVIDER_PRIORITY_APPLICATION,
     );
 
-    app.connect_activate(build_ui);
+    let overlay: Rc<RefCell<Option<Overlay>>> = Rc::default();
 
-    let app_clone = app.clone();
+    let overlay_clone = overlay.clone();
+    app.connect_activate(move |app| {
+        overlay_clone.replace(Some(build_ui(app)));
+    });
 
     gtk4::glib::MainContext::default().spawn_local(async move {
-        let context = context;
-
-        while let Some((w, h, x, y)) = rx.recv().await {
-            let window = app_clone
-                .windows()
-                .get(0)
-                .expect("Cannot get window")
-                .clone();
-
-            window.set_margin(gtk4_layer_shell::Edge::Left, x - context.offset_x);
-            window.set_margin(gtk4_layer_shell::Edge::Top, y - context.offset_y);
-
-            window.set_width_request(w);
-            window.set_default_width(w);
-            window.set_height_request(h);
-            window.set_default_height(h);
+        while let Some(msg) = rx.recv().await {
+            let Some(overlay) = overlay.borrow().clone() else {
+                println!("Indicator is not ready, dropping {msg:?}");
+                continue;
+            };
+
+            match msg {
+                IndicatorMsg::Show(region) => {
+                    let window = &overlay.window;
+                    let Region { x, y, w, h } = region;
+
+                    // monitors come and go, so look it up for every session
+                    let Some(monitor) = find_monitor(&region) else {
+                        println!("No monitor found for {region}");
+                        continue;
+                    };
+                    let geometry = monitor.geometry();
+
+                    // the monitor of a layer surface only changes when it is mapped again
+                    window.set_visible(false);
+                    window.set_monitor(Some(&monitor));
+
+                    window.set_margin(gtk4_layer_shell::Edge::Left, x - geometry.x() - BORDER);
+                    window.set_margin(gtk4_layer_shell::Edge::Top, y - geometry.y() - BORDER);
+
+                    overlay
+                        .outline
+                        .set_size_request(w + 2 * BORDER, h + 2 * BORDER);
+                    overlay.label.set_width_request(w + 2 * BORDER);
+                    window.set_default_size(w + 2 * BORDER, h + 2 * BORDER);
+
+                    overlay.label.set_text("");
+                    overlay.set_state_class("waiting");
+                    window.present();
+
+                    // let the clicks through to the game underneath
+                    if let Some(surface) = window.surface() {
+                        surface.set_input_region(&gtk4::cairo::Region::create());
+                    }
+                }
+
+                IndicatorMsg::State(state) => overlay.set_state_class(match state {
+                    FishingState::Hooked | FishingState::Reeling => "matched",
+                    FishingState::Paused => "paused",
+                    _ => "waiting",
+                }),
+
+                IndicatorMsg::Text(text) => overlay.label.set_text(text.trim()),
+
+                IndicatorMsg::Catch => {
+                    overlay.outline.add_css_class("flash");
+
+                    let outline = overlay.outline.clone();
+                    gtk4::glib::timeout_add_local_once(
+                        std::time::Duration::from_millis(FLASH_MS),
+                        move || outline.remove_css_class("flash"),
+                    );
+                }
+
+                IndicatorMsg::Error(e) => {
+                    overlay.set_state_class("error");
+                    overlay.label.set_text(&e);
+
+                    // a new session may have started in the meantime
+                    let overlay = overlay.clone();
+                    gtk4::glib::timeout_add_local_once(
+                        std::time::Duration::from_secs(ERROR_LINGER_SECS),
+                        move || {
+                            if overlay.outline.has_css_class("error") {
+                                overlay.window.set_visible(false);
+                            }
+                        },
+                    );
+                }
+
+                IndicatorMsg::Hide => overlay.window.set_visible(false),
+            }
         }
     });
 
-    app.run()
+    // the window stays hidden until a session starts, so keep the app alive without it
+    let _hold = app.hold();
+
+    // the arguments belong to auto_fishing, not to gtk
+    app.run_with_args::<&str>(&[])
 }
 
-fn build_ui(app: &Application) {
+fn build_ui(app: &Application) -> Overlay {
     // Create a window
     let window = ApplicationWindow::builder()
         .application(app)
-        .title("Layer Shell Demo")
+        .title("auto_fishing indicator")
         .build();
 
     window.init_layer_shell();
@@ -114,6 +242,25 @@ fn build_ui(app: &Application) {
     window.set_anchor(gtk4_layer_shell::Edge::Left, true);
     window.set_anchor(gtk4_layer_shell::Edge::Top, true);
 
-    // Show the window
-    window.present();
+    window.add_css_class("indicator");
+
+    // the outline surrounds the capture region, the text sits below it so OCR never sees it
+    let outline = gtk4::Box::new(gtk4::Orientation::Vertical, 0);
+    outline.add_css_class("outline");
+
+    let label = gtk4::Label::new(None);
+    label.add_css_class("ocr-text");
+    label.set_ellipsize(gtk4::pango::EllipsizeMode::End);
+    label.set_xalign(0.0);
+
+    let content = gtk4::Box::new(gtk4::Orientation::Vertical, 0);
+    content.append(&outline);
+    content.append(&label);
+    window.set_child(Some(&content));
+
+    Overlay {
+        window,
+        outline,
+        label,
+    }
 }
@@ -0,0 +1,424 @@
+use std::{
+    fmt,
+    str::FromStr,
+    sync::{Arc, Mutex},
+    time::Instant,
+};
+
+use iced::futures::future::BoxFuture;
+use serde::{Deserialize, Serialize};
+use smart_default::SmartDefault;
+
+use crate::fishing::FishingErr;
+
+/// Sends the presses and releases that reel in and recast the line.
+pub trait InputBackend: Send {
+    fn send(&mut self, target: Target, pressed: bool) -> BoxFuture<'_, Result<(), FishingErr>>;
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MouseButton {
+    Left,
+    Right,
+    Middle,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Target {
+    Mouse(MouseButton),
+    /// A Linux input event keycode, e.g. 30 for `A`
+    Key(u16),
+}
+
+/// What to press for the reel or recast action, written as
+/// `<left|right|middle|key:CODE>[@HOLD_MS][xREPEAT]`, e.g. `left`, `key:30@120x2`.
+#[derive(Debug, Clone, PartialEq, Eq, SmartDefault, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct ActionSpec {
+    #[default(Target::Mouse(MouseButton::Left))]
+    pub target: Target,
+    #[default(20)]
+    pub hold_ms: u64,
+    #[default(1)]
+    pub repeat: u32,
+}
+
+impl fmt::Display for ActionSpec {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.target {
+            Target::Mouse(MouseButton::Left) => write!(f, "left")?,
+            Target::Mouse(MouseButton::Right) => write!(f, "right")?,
+            Target::Mouse(MouseButton::Middle) => write!(f, "middle")?,
+            Target::Key(code) => write!(f, "key:{code}")?,
+        }
+
+        write!(f, "@{}", self.hold_ms)?;
+
+        if self.repeat != 1 {
+            write!(f, "x{}", self.repeat)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl FromStr for ActionSpec {
+    type Err = String;
+
+    fn from_str(raw: &str) -> Result<Self, Self::Err> {
+        let raw = raw.trim();
+        let mut spec = ActionSpec::default();
+
+        let (rest, repeat) = match raw.rsplit_once('x') {
+            Some((rest, repeat)) if !repeat.is_empty() && !rest.is_empty() => (rest, Some(repeat)),
+            _ => (raw, None),
+        };
+
+        let (target, hold) = match rest.split_once('@') {
+            Some((target, hold)) => (target, Some(hold)),
+            None => (rest, None),
+        };
+
+        spec.target = match target {
+            "left" => Target::Mouse(MouseButton::Left),
+            "right" => Target::Mouse(MouseButton::Right),
+            "middle" => Target::Mouse(MouseButton::Middle),
+            _ => match target.strip_prefix("key:").map(str::parse) {
+                Some(Ok(code)) if (KEY_MIN..=KEY_MAX).contains(&code) => Target::Key(code),
+                Some(Ok(code)) => {
+                    return Err(format!("Keycode {code} is outside {KEY_MIN}..={KEY_MAX}"));
+                }
+                _ => return Err(format!("Unknown action target: {target}")),
+            },
+        };
+
+        if let Some(hold) = hold {
+            spec.hold_ms = hold
+                .parse()
+                .map_err(|_| format!("Invalid hold time: {hold}"))?;
+        }
+
+        if let Some(repeat) = repeat {
+            spec.repeat = match repeat.parse() {
+                Ok(0) | Err(_) => return Err(format!("Invalid repeat count: {repeat}")),
+                Ok(repeat) => repeat,
+            };
+        }
+
+        Ok(spec)
+    }
+}
+
+impl TryFrom<String> for ActionSpec {
+    type Error = String;
+
+    fn try_from(raw: String) -> Result<Self, Self::Error> {
+        raw.parse()
+    }
+}
+
+impl From<ActionSpec> for String {
+    fn from(spec: ActionSpec) -> Self {
+        spec.to_string()
+    }
+}
+
+/// The range of keyboard keycodes an action may send
+pub const KEY_MIN: u16 = 1;
+pub const KEY_MAX: u16 = 248;
+
+/// Gap between two repeats of the same action
+const REPEAT_GAP: tokio::time::Duration = tokio::time::Duration::from_millis(50);
+
+pub async fn perform(input: &mut dyn InputBackend, action: &ActionSpec) -> Result<(), FishingErr> {
+    for i in 0..action.repeat {
+        if i > 0 {
+            tokio::time::sleep(REPEAT_GAP).await;
+        }
+
+        input.send(action.target, true).await?;
+        tokio::time::sleep(tokio::time::Duration::from_millis(action.hold_ms)).await;
+        input.send(action.target, false).await?;
+    }
+
+    Ok(())
+}
+
+#[derive(Debug, Clone, SmartDefault, Serialize, Deserialize)]
+pub enum InputKind {
+    #[cfg_attr(not(target_os = "linux"), default)]
+    Ydotool,
+    /// A virtual mouse created directly through `/dev/uinput`, no daemon needed
+    #[cfg(target_os = "linux")]
+    #[cfg_attr(target_os = "linux", default)]
+    Uinput,
+    /// Records the clicks without sending anything
+    Mock,
+}
+
+impl InputKind {
+    pub fn create(&self) -> Result<Box<dyn InputBackend>, FishingErr> {
+        match self {
+            InputKind::Ydotool => Ok(Box::new(YdotoolInput)),
+            #[cfg(target_os = "linux")]
+            InputKind::Uinput => Ok(Box::new(UinputInput::new()?)),
+            InputKind::Mock => Ok(Box::new(MockInput::default())),
+        }
+    }
+}
+
+/// Shells out to `ydotool`, which needs `ydotoold` to be running.
+pub struct YdotoolInput;
+
+impl InputBackend for YdotoolInput {
+    fn send(&mut self, target: Target, pressed: bool) -> BoxFuture<'_, Result<(), FishingErr>> {
+        Box::pin(async move {
+            let mut cmd = tokio::process::Command::new("ydotool");
+
+            match target {
+                Target::Mouse(button) => {
+                    let code = match button {
+                        MouseButton::Left => 0x00,
+                        MouseButton::Right => 0x01,
+                        MouseButton::Middle => 0x02,
+                    };
+                    let flag = if pressed { 0x40 } else { 0x80 };
+
+                    cmd.arg("click").arg(format!("{:#04X}", code | flag));
+                }
+                Target::Key(code) => {
+                    cmd.arg("key").arg(format!("{code}:{}", u8::from(pressed)));
+                }
+            }
+
+            let out = cmd
+                .output()
+                .await
+                .map_err(|e| FishingErr::InputErr(format!("Cannot run ydotool: {e}")))?;
+
+            if !out.status.success() {
+                return Err(FishingErr::InputErr(format!(
+                    "ydotool failed: {}",
+                    String::from_utf8_lossy(&out.stderr).trim()
+                )));
+            }
+
+            Ok(())
+        })
+    }
+}
+
+/// An in-process virtual mouse and keyboard, created once per session.
+#[cfg(target_os = "linux")]
+pub struct UinputInput {
+    device: evdev::uinput::VirtualDevice,
+}
+
+#[cfg(target_os = "linux")]
+impl UinputInput {
+    pub fn new() -> Result<Self, FishingErr> {
+        use evdev::{AttributeSet, KeyCode, RelativeAxisCode, uinput::VirtualDevice};
+
+        let map_err = |e: std::io::Error| match e.kind() {
+            std::io::ErrorKind::PermissionDenied => FishingErr::InputErr(permission_hint()),
+            std::io::ErrorKind::NotFound => FishingErr::InputErr(
+                "/dev/uinput does not exist, load the module with `sudo modprobe uinput`".into(),
+            ),
+            _ => FishingErr::InputErr(format!("Cannot create uinput device: {e}")),
+        };
+
+        // the relative axes make compositors treat the device as a pointer
+        let device = VirtualDevice::builder()
+            .map_err(map_err)?
+            .name("auto_fishing")
+            .with_keys(&AttributeSet::from_iter(
+                [KeyCode::BTN_LEFT, KeyCode::BTN_RIGHT, KeyCode::BTN_MIDDLE]
+                    .into_iter()
+                    .chain((KEY_MIN..=KEY_MAX).map(KeyCode)),
+            ))
+            .map_err(map_err)?
+            .with_relative_axes(&AttributeSet::from_iter([
+                RelativeAxisCode::REL_X,
+                RelativeAxisCode::REL_Y,
+            ]))
+            .map_err(map_err)?
+            .build()
+            .map_err(map_err)?;
+
+        Ok(Self { device })
+    }
+
+    fn emit(&mut self, code: evdev::KeyCode, value: i32) -> Result<(), FishingErr> {
+        let event = evdev::InputEvent::new(evdev::EventType::KEY.0, code.0, value);
+
+        self.device
+            .emit(&[event])
+            .map_err(|e| FishingErr::InputErr(format!("Cannot send uinput event: {e}")))
+    }
+}
+
+#[cfg(target_os = "linux")]
+impl InputBackend for UinputInput {
+    fn send(&mut self, target: Target, pressed: bool) -> BoxFuture<'_, Result<(), FishingErr>> {
+        Box::pin(async move {
+            let code = match target {
+                Target::Mouse(MouseButton::Left) => evdev::KeyCode::BTN_LEFT,
+                Target::Mouse(MouseButton::Right) => evdev::KeyCode::BTN_RIGHT,
+                Target::Mouse(MouseButton::Middle) => evdev::KeyCode::BTN_MIDDLE,
+                Target::Key(code) if (KEY_MIN..=KEY_MAX).contains(&code) => evdev::KeyCode(code),
+                Target::Key(code) => {
+                    return Err(FishingErr::InputErr(format!(
+                        "Keycode {code} is outside {KEY_MIN}..={KEY_MAX}"
+                    )));
+                }
+            };
+
+            self.emit(code, i32::from(pressed))
+        })
+    }
+}
+
+#[cfg(target_os = "linux")]
+pub fn permission_hint() -> String {
+    match in_input_group() {
+        Some(false) => "Permission denied on /dev/uinput: your user is not in the `input` group. \
+            Run `sudo usermod -aG input $USER` and log in again"
+            .into(),
+        _ => "Permission denied on /dev/uinput: make sure a udev rule gives the `input` group \
+            access, e.g. KERNEL==\"uinput\", GROUP=\"input\", MODE=\"0660\""
+            .into(),
+    }
+}
+
+/// Checks the groups of this process for `input`, or `None` if they cannot be read.
+#[cfg(target_os = "linux")]
+fn in_input_group() -> Option<bool> {
+    let groups = std::fs::read_to_string("/etc/group").ok()?;
+    // name:password:gid:members
+    let gid = groups.lines().find_map(|line| {
+        let fields: Vec<&str> = line.split(':').collect();
+        match fields.as_slice() {
+            ["input", _, gid, ..] => gid.parse::<u32>().ok(),
+            _ => None,
+        }
+    })?;
+
+    let status = std::fs::read_to_string("/proc/self/status").ok()?;
+    let own = status
+        .lines()
+        .find_map(|line| line.strip_prefix("Groups:"))?
+        .split_whitespace()
+        .filter_map(|g| g.parse::<u32>().ok())
+        .any(|g| g == gid);
+
+    Some(own)
+}
+
+/// Keeps every press and release so a session can be inspected without a game.
+#[derive(Default)]
+pub struct MockInput {
+    events: Arc<Mutex<Vec<(Instant, Target, bool)>>>,
+}
+
+impl MockInput {
+    pub fn recorded(&self) -> Arc<Mutex<Vec<(Instant, Target, bool)>>> {
+        self.events.clone()
+    }
+}
+
+impl InputBackend for MockInput {
+    fn send(&mut self, target: Target, pressed: bool) -> BoxFuture<'_, Result<(), FishingErr>> {
+        Box::pin(async move {
+            println!("Mock input: {target:?} pressed={pressed}");
+
+            self.events
+                .lock()
+                .map_err(|e| FishingErr::InputErr(e.to_string()))?
+                .push((Instant::now(), target, pressed));
+
+            Ok(())
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+
+    #[test]
+    fn parse_action_specs() {
+        assert_eq!("left".parse(), Ok(ActionSpec::default()));
+        assert_eq!(
+            "key:30@120x2".parse(),
+            Ok(ActionSpec {
+                target: Target::Key(30),
+                hold_ms: 120,
+                repeat: 2,
+            })
+        );
+        assert_eq!(
+            " middle@5 ".parse::<ActionSpec>().map(|spec| spec.target),
+            Ok(Target::Mouse(MouseButton::Middle))
+        );
+    }
+
+    #[test]
+    fn reject_invalid_action_specs() {
+        for raw in [
+            "", "up", "key:", "key:abc", "left@x", "key:30x0", "key:0", "key:300",
+        ] {
+            assert!(raw.parse::<ActionSpec>().is_err(), "{raw} should not parse");
+        }
+    }
+
+    #[tokio::test]
+    async fn perform_presses_holds_and_releases_each_repeat() {
+        let mut mock = MockInput::default();
+        let recorded = mock.recorded();
+        let action = ActionSpec {
+            target: Target::Key(30),
+            hold_ms: 30,
+            repeat: 3,
+        };
+
+        perform(&mut mock, &action).await.unwrap();
+
+        let events = recorded.lock().unwrap();
+        assert_eq!(events.len(), 6);
+
+        let mut last_release = None;
+
+        for pair in events.chunks(2) {
+            let [(pressed_at, press, true), (released_at, release, false)] = pair else {
+                panic!("expected a press then a release, got {pair:?}");
+            };
+
+            assert_eq!(*press, Target::Key(30));
+            assert_eq!(*release, Target::Key(30));
+            assert!(*released_at - *pressed_at >= Duration::from_millis(action.hold_ms));
+
+            if let Some(last_release) = last_release {
+                assert!(*pressed_at - last_release >= REPEAT_GAP);
+            }
+
+            last_release = Some(*released_at);
+        }
+    }
+
+    #[test]
+    fn format_and_parse_round_trip() {
+        for raw in [
+            "left@20",
+            "right@0x3",
+            "middle@1000",
+            "key:1@20",
+            "key:248@50x10",
+        ] {
+            let spec: ActionSpec = raw.parse().unwrap();
+            assert_eq!(spec.to_string(), raw);
+            assert_eq!(spec.to_string().parse(), Ok(spec));
+        }
+    }
+}
@@ -0,0 +1,233 @@
+use std::{
+    path::PathBuf,
+    sync::{Arc, Mutex},
+};
+
+use iced::futures::{SinkExt, channel::mpsc::Sender};
+use serde::{Deserialize, Serialize};
+use tokio::{
+    io::{AsyncBufReadExt, AsyncWriteExt, BufReader},
+    net::{UnixListener, UnixStream},
+    sync::{broadcast, oneshot},
+};
+
+use crate::{fishing::FishingEvt, region::Region};
+
+/// One line sent by a client, e.g. `{"cmd":"start"}` or
+/// `{"cmd":"set-args","args":{"keyword":"Ebonkoi,Golden Carp"}}`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "cmd", rename_all = "kebab-case")]
+pub enum Request {
+    Start,
+    Stop,
+    /// Overrides the given `FishingArgs` fields, used from the next session on
+    SetArgs {
+        args: serde_json::Map<String, serde_json::Value>,
+    },
+    GetStatus,
+    /// Keeps the connection open and streams every [`Event`]
+    Subscribe,
+}
+
+/// One line sent back, in answer to a request or as a subscribed event.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "kebab-case")]
+pub enum Response {
+    Ok,
+    Status(Status),
+    Event(Event),
+    Error { message: String },
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Status {
+    pub running: bool,
+    pub paused: bool,
+    pub stopping: bool,
+    pub state: Option<String>,
+    pub region: Option<Region>,
+    pub keyword: String,
+    pub uptime_secs: u64,
+    pub catches: u32,
+    pub finish_reason: String,
+    pub error: String,
+}
+
+/// The session events worth forwarding, without the per-frame noise.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "event", content = "value", rename_all = "kebab-case")]
+pub enum Event {
+    CountDown(i32),
+    State(String),
+    Matched(String),
+    TimeoutRecast,
+    Finished(String),
+    Error(String),
+}
+
+impl Event {
+    pub fn from_evt(evt: &FishingEvt) -> Option<Self> {
+        match evt {
+            FishingEvt::CountDown(num) => Some(Event::CountDown(*num)),
+            FishingEvt::State(state) => Some(Event::State(state.to_string())),
+            FishingEvt::Matched(source) => Some(Event::Matched(source.clone())),
+            FishingEvt::TimeoutRecast => Some(Event::TimeoutRecast),
+            FishingEvt::Finished(reason) => Some(Event::Finished(reason.to_string())),
+            // errors arrive as `Message::FishingErr` either way and are sent from there
+            _ => None,
+        }
+    }
+}
+
+/// Answers one request, can be cloned into a `Message` but only replies once
+#[derive(Debug, Clone)]
+pub struct Reply(Arc<Mutex<Option<oneshot::Sender<Response>>>>);
+
+impl Reply {
+    pub fn send(&self, response: Response) {
+        let sender = self.0.lock().ok().and_then(|mut sender| sender.take());
+
+        if let Some(sender) = sender {
+            let _ = sender.send(response);
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum IpcEvents {
+    Request(Request, Reply),
+    PassSender(broadcast::Sender<Event>),
+    Err(String),
+}
+
+/// The socket lives in `$XDG_RUNTIME_DIR`, which only the user can enter
+pub fn socket_path() -> Result<PathBuf, std::io::Error> {
+    match std::env::var("XDG_RUNTIME_DIR") {
+        Ok(dir) if !dir.is_empty() => Ok(PathBuf::from(dir).join("auto_fishing.sock")),
+        _ => Err(std::io::Error::new(
+            std::io::ErrorKind::NotFound,
+            "XDG_RUNTIME_DIR is not set, the control socket needs a private directory",
+        )),
+    }
+}
+
+pub async fn serve(mut tx: Sender<IpcEvents>) -> Result<(), std::io::Error> {
+    use std::os::unix::fs::{FileTypeExt, PermissionsExt};
+
+    let path = socket_path()?;
+
+    // a socket nobody answers on is left over from a crashed instance
+    if let Ok(meta) = std::fs::symlink_metadata(&path) {
+        if !meta.file_type().is_socket() {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::AlreadyExists,
+                format!("{} exists and is not a socket", path.display()),
+            ));
+        }
+
+        if UnixStream::connect(&path).await.is_ok() {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::AddrInUse,
+                format!("Another instance is listening on {}", path.display()),
+            ));
+        }
+
+        std::fs::remove_file(&path)?;
+    }
+
+    let listener = UnixListener::bind(&path)?;
+    std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o600))?;
+
+    println!("Listening on {}", path.display());
+
+    let (events_tx, _) = broadcast::channel(64);
+
+    tx.send(IpcEvents::PassSender(events_tx.clone()))
+        .await
+        .unwrap_or_else(|e| {
+            println!("Cannot send: {e}");
+        });
+
+    loop {
+        let (stream, _) = listener.accept().await?;
+        let tx = tx.clone();
+        let events_tx = events_tx.clone();
+
+        tokio::spawn(async move {
+            if let Err(e) = handle_client(stream, tx, events_tx).await {
+                println!("Control client failed: {e}");
+            }
+        });
+    }
+}
+
+async fn handle_client(
+    stream: UnixStream,
+    mut tx: Sender<IpcEvents>,
+    events_tx: broadcast::Sender<Event>,
+) -> Result<(), std::io::Error> {
+    let (reader, mut writer) = stream.into_split();
+    let mut lines = BufReader::new(reader).lines();
+
+    while let Some(line) = lines.next_line().await? {
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        let request = match serde_json::from_str::<Request>(&line) {
+            Ok(request) => request,
+            Err(e) => {
+                let message = format!("Invalid request: {e}");
+                write_response(&mut writer, &Response::Error { message }).await?;
+                continue;
+            }
+        };
+
+        if let Request::Subscribe = request {
+            let mut events_rx = events_tx.subscribe();
+            write_response(&mut writer, &Response::Ok).await?;
+
+            // only ends once the client hangs up, anything it sends meanwhile is ignored
+            loop {
+                tokio::select! {
+                    line = lines.next_line() => {
+                        if line?.is_none() {
+                            return Ok(());
+                        }
+                    }
+                    event = events_rx.recv() => match event {
+                        Ok(event) => write_response(&mut writer, &Response::Event(event)).await?,
+                        Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                            println!("Control client lagged, skipped {skipped} events");
+                        }
+                        Err(broadcast::error::RecvError::Closed) => return Ok(()),
+                    },
+                }
+            }
+        }
+
+        let (reply_tx, reply_rx) = oneshot::channel();
+        let reply = Reply(Arc::new(Mutex::new(Some(reply_tx))));
+
+        tx.send(IpcEvents::Request(request, reply))
+            .await
+            .map_err(std::io::Error::other)?;
+
+        let response = reply_rx.await.unwrap_or_else(|_| Response::Error {
+            message: "The app dropped the request".into(),
+        });
+
+        write_response(&mut writer, &response).await?;
+    }
+
+    Ok(())
+}
+
+async fn write_response(
+    writer: &mut tokio::net::unix::OwnedWriteHalf,
+    response: &Response,
+) -> Result<(), std::io::Error> {
+    let mut line = serde_json::to_string(response)?;
+    line.push('\n');
+    writer.write_all(line.as_bytes()).await
+}
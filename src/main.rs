@@ -2,27 +2,51 @@ use app::Fishing;
 use iced::Theme;
 
 pub mod app;
+pub mod capture;
+pub mod catch_log;
+pub mod cli;
+pub mod config;
+pub mod diagnostics;
 pub mod fishing;
 pub mod indicator;
+pub mod input;
+pub mod ipc;
+pub mod matcher;
+pub mod ocr;
+pub mod region;
+pub mod state;
+pub mod stats;
+pub mod stop;
+pub mod timing;
 pub mod tray;
 pub mod window;
 
-fn main() -> Result<(), Box<dyn std::error::Error>> {
-    let (tx, rx) = tokio::sync::mpsc::channel::<(i32, i32, i32, i32)>(1);
+fn main() {
+    // returning the error from main would print it with Debug and hide the usage text
+    if let Err(e) = run() {
+        eprintln!("Error: {e}");
+        std::process::exit(1);
+    }
+}
+
+fn run() -> Result<(), Box<dyn std::error::Error>> {
+    match cli::parse_args(std::env::args().skip(1))? {
+        cli::Command::Gui => {}
+        cli::Command::Headless(args) => return cli::run_headless(args),
+        cli::Command::Ctl(request) => return cli::run_ctl(request),
+        cli::Command::Help => {
+            cli::print_usage();
+            return Ok(());
+        }
+    }
+
+    let (tx, rx) = tokio::sync::mpsc::channel::<indicator::IndicatorMsg>(16);
 
-    // std::thread::Builder::new()
-    //     .name("auto_fishing_gtk".into())
-    //     .spawn(|| {
-    //         let rt = tokio::runtime::Builder::new_multi_thread()
-    //             .enable_time()
-    //             .build()
-    //             .expect("Cannot build runtime");
-    //
-    //         rt.block_on(async move {
-    //             indicator::start_gtk(rx);
-    //         });
-    //     })
-    //     .expect("Failed to spawn gtk");
+    std::thread::Builder::new()
+        .name("auto_fishing_gtk".into())
+        .spawn(|| {
+            indicator::start_gtk(rx);
+        })?;
 
     iced::daemon("fishing", Fishing::update, Fishing::view)
         .subscription(Fishing::subscription)
@@ -0,0 +1,354 @@
+use regex::{Regex, RegexBuilder};
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum RuleErr {
+    #[error("Empty rule at position {0}")]
+    Empty(usize),
+    #[error("Unterminated regex in rule {0}")]
+    Unterminated(String),
+    #[error("Unknown regex flag '{1}' in rule {0}")]
+    UnknownFlag(String, char),
+    #[error("Invalid regex in rule {0}: {1}")]
+    Regex(String, regex::Error),
+    #[error("No include rule in {0:?}")]
+    NoInclude(String),
+}
+
+/// Keywords shorter than this are matched exactly unless they carry a `~N` suffix
+pub const MIN_FUZZY_LEN: usize = 6;
+
+#[derive(Debug, Clone)]
+enum Pattern {
+    Fuzzy {
+        normalized: Vec<char>,
+        max_distance: usize,
+    },
+    Regex(Regex),
+}
+
+/// One include or exclude rule parsed from the keyword field.
+#[derive(Debug, Clone)]
+pub struct Rule {
+    pub source: String,
+    pub exclude: bool,
+    pattern: Pattern,
+}
+
+impl Rule {
+    pub fn max_distance(&self) -> usize {
+        match &self.pattern {
+            Pattern::Fuzzy { max_distance, .. } => *max_distance,
+            Pattern::Regex(_) => 0,
+        }
+    }
+
+    fn distance(&self, text: &str, normalized: &[char]) -> usize {
+        match &self.pattern {
+            Pattern::Fuzzy {
+                normalized: needle, ..
+            } => substring_distance(needle, normalized),
+            Pattern::Regex(regex) => usize::from(!regex.is_match(text)),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Match<'a> {
+    pub rule: &'a Rule,
+    pub distance: usize,
+}
+
+impl Match<'_> {
+    pub fn is_hit(&self) -> bool {
+        self.distance <= self.rule.max_distance()
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Evaluation<'a> {
+    /// The include rule closest to matching, hit or not
+    pub best: Option<Match<'a>>,
+    pub excluded_by: Option<&'a Rule>,
+}
+
+impl Evaluation<'_> {
+    pub fn is_hit(&self) -> bool {
+        self.excluded_by.is_none() && self.best.as_ref().is_some_and(Match::is_hit)
+    }
+}
+
+/// Matches OCR output against the catch rules, tolerating misreads.
+///
+/// The keyword field is a comma separated list of rules:
+/// - `Ebonkoi` matches fuzzily and case-insensitively, `Ebonkoi~2` overrides the allowed distance.
+///   The default distance only applies from [`MIN_FUZZY_LEN`] characters on, shorter keywords
+///   would match too many unrelated words
+/// - `/Golden (Carp|Crate)/` is a regex, `/crate/i` one that ignores case
+/// - a leading `!` turns any rule into an exclusion, e.g. `Crate,!Wooden Crate`
+#[derive(Debug, Clone, Default)]
+pub struct Matcher {
+    rules: Vec<Rule>,
+}
+
+impl Matcher {
+    pub fn parse(raw: &str, default_distance: usize) -> Result<Self, RuleErr> {
+        let mut rules = vec![];
+        let mut rest = raw;
+        let mut position = 0;
+
+        loop {
+            position += 1;
+            rest = rest.trim_start();
+
+            let (exclude, body) = match rest.strip_prefix('!') {
+                Some(body) => (true, body.trim_start()),
+                None => (false, rest),
+            };
+
+            let (rule, remaining) = match body.strip_prefix('/') {
+                Some(body) => parse_regex(body, exclude)?,
+                None => parse_fuzzy(body, exclude, default_distance, position)?,
+            };
+
+            rules.push(rule);
+
+            match remaining.trim_start().strip_prefix(',') {
+                Some(remaining) if remaining.trim().is_empty() => break,
+                Some(remaining) => rest = remaining,
+                None if remaining.trim().is_empty() => break,
+                None => return Err(RuleErr::Unterminated(remaining.trim().to_string())),
+            }
+        }
+
+        if rules.iter().all(|rule| rule.exclude) {
+            return Err(RuleErr::NoInclude(raw.to_string()));
+        }
+
+        Ok(Self { rules })
+    }
+
+    pub fn evaluate(&self, text: &str) -> Evaluation<'_> {
+        let normalized = normalize(text);
+
+        let excluded_by = self
+            .rules
+            .iter()
+            .filter(|rule| rule.exclude)
+            .find(|rule| rule.distance(text, &normalized) <= rule.max_distance());
+
+        let best = self
+            .rules
+            .iter()
+            .filter(|rule| !rule.exclude)
+            .map(|rule| Match {
+                rule,
+                distance: rule.distance(text, &normalized),
+            })
+            .min_by_key(|m| m.distance as isize - m.rule.max_distance() as isize);
+
+        Evaluation { best, excluded_by }
+    }
+}
+
+fn parse_regex(body: &str, exclude: bool) -> Result<(Rule, &str), RuleErr> {
+    let mut escaped = false;
+    let end = body.char_indices().find_map(|(i, c)| match c {
+        '\\' if !escaped => {
+            escaped = true;
+            None
+        }
+        '/' if !escaped => Some(i),
+        _ => {
+            escaped = false;
+            None
+        }
+    });
+
+    let Some(end) = end else {
+        return Err(RuleErr::Unterminated(format!("/{body}")));
+    };
+
+    let pattern = &body[..end];
+    let after = &body[end + 1..];
+    let flags_len = after.find(',').unwrap_or(after.len());
+    let flags = after[..flags_len].trim_end();
+    let source = format!("{}/{pattern}/{flags}", if exclude { "!" } else { "" });
+
+    let mut builder = RegexBuilder::new(pattern);
+
+    for flag in flags.chars() {
+        match flag {
+            'i' => builder.case_insensitive(true),
+            c => return Err(RuleErr::UnknownFlag(source, c)),
+        };
+    }
+
+    let regex = builder
+        .build()
+        .map_err(|e| RuleErr::Regex(source.clone(), e))?;
+
+    let rule = Rule {
+        source,
+        exclude,
+        pattern: Pattern::Regex(regex),
+    };
+
+    Ok((rule, &after[flags_len..]))
+}
+
+fn parse_fuzzy(
+    body: &str,
+    exclude: bool,
+    default_distance: usize,
+    position: usize,
+) -> Result<(Rule, &str), RuleErr> {
+    let len = body.find(',').unwrap_or(body.len());
+    let kwd = body[..len].trim();
+
+    let (text, max_distance) = match kwd.rsplit_once('~') {
+        Some((text, distance)) => match distance.parse() {
+            Ok(distance) => (text.trim(), Some(distance)),
+            Err(_) => (kwd, None),
+        },
+        None => (kwd, None),
+    };
+
+    if text.is_empty() {
+        return Err(RuleErr::Empty(position));
+    }
+
+    let normalized = normalize(text);
+    let max_distance = max_distance.unwrap_or(if normalized.len() >= MIN_FUZZY_LEN {
+        default_distance
+    } else {
+        0
+    });
+
+    let rule = Rule {
+        source: format!("{}{kwd}", if exclude { "!" } else { "" }),
+        exclude,
+        pattern: Pattern::Fuzzy {
+            normalized,
+            max_distance,
+        },
+    };
+
+    Ok((rule, &body[len..]))
+}
+
+/// Folds case and the characters tesseract commonly confuses into one form.
+fn normalize(text: &str) -> Vec<char> {
+    text.chars()
+        .filter(|c| !c.is_whitespace())
+        .map(|c| match c.to_ascii_lowercase() {
+            '0' => 'o',
+            '1' | 'i' | '|' | '!' => 'l',
+            '5' => 's',
+            '8' => 'b',
+            '2' => 'z',
+            c => c,
+        })
+        .collect()
+}
+
+/// Edit distance between `needle` and its best matching substring of `haystack`.
+fn substring_distance(needle: &[char], haystack: &[char]) -> usize {
+    // row[j] is the cost of matching the needle prefix so far ending at haystack[j]
+    let mut row = vec![0; haystack.len() + 1];
+
+    for (i, n) in needle.iter().enumerate() {
+        let mut diagonal = row[0];
+        row[0] = i + 1;
+
+        for (j, h) in haystack.iter().enumerate() {
+            let substitution = diagonal + usize::from(n != h);
+            diagonal = row[j + 1];
+            row[j + 1] = substitution.min(row[j] + 1).min(diagonal + 1);
+        }
+    }
+
+    row.into_iter().min().unwrap_or(needle.len())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn distance(needle: &str, haystack: &str) -> usize {
+        substring_distance(&normalize(needle), &normalize(haystack))
+    }
+
+    fn hit(rules: &str, default_distance: usize, text: &str) -> bool {
+        Matcher::parse(rules, default_distance)
+            .expect("valid rules")
+            .evaluate(text)
+            .is_hit()
+    }
+
+    #[test]
+    fn normalize_folds_case_whitespace_and_ocr_confusions() {
+        assert_eq!(normalize("G0lden Carp"), normalize("golden carp"));
+        assert_eq!(normalize("1l|i!"), vec!['l'; 5]);
+        assert_eq!(normalize("B5 Z2 8"), normalize("bsz zb"));
+    }
+
+    #[test]
+    fn substring_distance_finds_the_best_window() {
+        assert_eq!(distance("Ebonkoi", "You caught Ebonkoi!"), 0);
+        assert_eq!(distance("Ebonkoi", "You caught Ebonkai"), 1);
+        assert_eq!(distance("Crate", "Pirate"), 1);
+        assert_eq!(distance("Carp", ""), 4);
+        assert_eq!(distance("", "anything"), 0);
+    }
+
+    #[test]
+    fn default_distance_skips_short_keywords() {
+        assert!(!hit("Crate", 1, "Pirate"));
+        assert!(!hit("Bass", 1, "Glass"));
+        assert!(hit("Crate~1", 1, "Pirate"));
+        assert!(hit("Ebonkoi", 1, "Ebonkai"));
+        assert!(!hit("Ebonkoi", 0, "Ebonkai"));
+    }
+
+    #[test]
+    fn evaluate_picks_the_closest_rule() {
+        let matcher = Matcher::parse("Ebonkoi, Golden Carp~2", 0).unwrap();
+        let evaluation = matcher.evaluate("G0lden Crap");
+
+        let best = evaluation.best.as_ref().unwrap();
+        assert_eq!(best.rule.source, "Golden Carp~2");
+        assert!(evaluation.is_hit());
+    }
+
+    #[test]
+    fn evaluate_regex_rules() {
+        assert!(hit("/golden (carp|crate)/i", 0, "A Golden Crate"));
+        assert!(!hit("/golden (carp|crate)/", 0, "A Golden Crate"));
+    }
+
+    #[test]
+    fn exclude_rules_veto_a_hit() {
+        let matcher = Matcher::parse("Crate, !Wooden Crate", 0).unwrap();
+
+        let evaluation = matcher.evaluate("Wooden Crate");
+        assert_eq!(evaluation.excluded_by.unwrap().source, "!Wooden Crate");
+        assert!(!evaluation.is_hit());
+
+        assert!(matcher.evaluate("Iron Crate").is_hit());
+    }
+
+    #[test]
+    fn parse_rejects_bad_rules() {
+        assert!(matches!(Matcher::parse("a,,b", 0), Err(RuleErr::Empty(2))));
+        assert!(matches!(
+            Matcher::parse("/open", 0),
+            Err(RuleErr::Unterminated(_))
+        ));
+        assert!(matches!(
+            Matcher::parse("!Crate", 0),
+            Err(RuleErr::NoInclude(_))
+        ));
+        assert!(Matcher::parse("Crate,", 0).is_ok());
+    }
+}
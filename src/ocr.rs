@@ -0,0 +1,45 @@
+use std::time::Instant;
+
+use image::RgbaImage;
+use tesseract::Tesseract;
+
+use crate::fishing::FishingErr;
+
+/// A Tesseract engine that is initialised once and fed every frame of a session.
+pub struct OcrEngine {
+    // `set_frame` consumes the engine, so it is taken out and put back for every frame
+    inner: Option<Tesseract>,
+}
+
+impl OcrEngine {
+    pub fn new() -> Result<Self, FishingErr> {
+        let start = Instant::now();
+        let inner = Tesseract::new(None, Some("eng"))?;
+
+        println!("OCR engine initialised in {:?}", start.elapsed());
+
+        Ok(Self { inner: Some(inner) })
+    }
+
+    pub fn recognize(&mut self, frame: &RgbaImage) -> Result<String, FishingErr> {
+        let Some(engine) = self.inner.take() else {
+            return Err(FishingErr::String(
+                "OCR engine is unavailable after a failed frame".into(),
+            ));
+        };
+
+        let (width, height) = frame.dimensions();
+        let mut engine = engine.set_frame(
+            frame.as_raw(),
+            width as i32,
+            height as i32,
+            4,
+            4 * width as i32,
+        )?;
+
+        let text = engine.get_text();
+        self.inner = Some(engine);
+
+        Ok(text?)
+    }
+}
@@ -0,0 +1,158 @@
+use std::{fmt, str::FromStr};
+
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+#[derive(Debug, Clone, Error)]
+pub enum RegionErr {
+    #[error("No capture region selected")]
+    Missing,
+    #[error("Region selection was cancelled")]
+    Cancelled,
+    #[error("Region {0:?} is not in the \"x,y wxh\" format")]
+    Format(String),
+    #[error("Region {0:?} contains an invalid number")]
+    Number(String),
+    #[error("Region {0} has an empty size")]
+    Empty(Region),
+    #[error("Region {0} reaches past the coordinate range")]
+    Overflow(Region),
+}
+
+/// A capture area in compositor coordinates, as printed by slurp.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct Region {
+    pub x: i32,
+    pub y: i32,
+    pub w: i32,
+    pub h: i32,
+}
+
+impl fmt::Display for Region {
+    /// Formats the region as a grim/slurp geometry
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{},{} {}x{}", self.x, self.y, self.w, self.h)
+    }
+}
+
+impl FromStr for Region {
+    type Err = RegionErr;
+
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        let input = input.trim();
+
+        if input.is_empty() {
+            return Err(RegionErr::Cancelled);
+        }
+
+        let format_err = || RegionErr::Format(input.to_string());
+        let number = |n: &str| {
+            n.parse::<i32>()
+                .map_err(|_| RegionErr::Number(input.to_string()))
+        };
+
+        let (position, size) = input.split_once(' ').ok_or_else(format_err)?;
+        let (x, y) = position.split_once(',').ok_or_else(format_err)?;
+        let (w, h) = size.trim().split_once('x').ok_or_else(format_err)?;
+
+        let region = Region {
+            x: number(x)?,
+            y: number(y)?,
+            w: number(w)?,
+            h: number(h)?,
+        };
+
+        if region.w <= 0 || region.h <= 0 {
+            return Err(RegionErr::Empty(region));
+        }
+
+        // keeps the far edge and the center representable as i32
+        if region.x.checked_add(region.w).is_none() || region.y.checked_add(region.h).is_none() {
+            return Err(RegionErr::Overflow(region));
+        }
+
+        Ok(region)
+    }
+}
+
+impl TryFrom<String> for Region {
+    type Error = RegionErr;
+
+    fn try_from(raw: String) -> Result<Self, Self::Error> {
+        raw.parse()
+    }
+}
+
+impl From<Region> for String {
+    fn from(region: Region) -> Self {
+        region.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_slurp_output() {
+        assert_eq!(
+            "10,20 300x40\n".parse::<Region>().unwrap(),
+            Region {
+                x: 10,
+                y: 20,
+                w: 300,
+                h: 40,
+            }
+        );
+        assert_eq!("-1920,0 5x5".parse::<Region>().map(|r| r.x).unwrap(), -1920);
+    }
+
+    #[test]
+    fn reject_malformed_regions() {
+        assert!(matches!("".parse::<Region>(), Err(RegionErr::Cancelled)));
+        assert!(matches!("  ".parse::<Region>(), Err(RegionErr::Cancelled)));
+        assert!(matches!(
+            "10,20".parse::<Region>(),
+            Err(RegionErr::Format(_))
+        ));
+        assert!(matches!(
+            "10 20 300x40".parse::<Region>(),
+            Err(RegionErr::Format(_))
+        ));
+        assert!(matches!(
+            "10,a 300x40".parse::<Region>(),
+            Err(RegionErr::Number(_))
+        ));
+        assert!(matches!(
+            "10,20 0x40".parse::<Region>(),
+            Err(RegionErr::Empty(_))
+        ));
+        assert!(matches!(
+            "10,20 30x-4".parse::<Region>(),
+            Err(RegionErr::Empty(_))
+        ));
+        assert!(matches!(
+            "2147483000,0 1000x10".parse::<Region>(),
+            Err(RegionErr::Overflow(_))
+        ));
+        assert!(matches!(
+            "0,2147483647 10x1".parse::<Region>(),
+            Err(RegionErr::Overflow(_))
+        ));
+        assert!("-2147483648,0 2147483647x1".parse::<Region>().is_ok());
+    }
+
+    #[test]
+    fn display_round_trips() {
+        let region = Region {
+            x: 5,
+            y: -7,
+            w: 640,
+            h: 48,
+        };
+
+        assert_eq!(region.to_string(), "5,-7 640x48");
+        assert_eq!(region.to_string().parse::<Region>().unwrap(), region);
+    }
+}
@@ -0,0 +1,51 @@
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+use smart_default::SmartDefault;
+
+/// What the bot believes is currently happening to the line.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FishingState {
+    /// Counting down before the session starts
+    Idle,
+    /// The line was just cast and the bobber is settling
+    Casting,
+    /// Polling the capture region for a catch keyword
+    Waiting,
+    /// A keyword matched, waiting before reeling in
+    Hooked,
+    /// The reel click was sent
+    Reeling,
+    /// The recast click was sent
+    Recasting,
+    /// Waiting for the app to resume or stop the session
+    Paused,
+}
+
+impl fmt::Display for FishingState {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            FishingState::Idle => "Idle",
+            FishingState::Casting => "Casting",
+            FishingState::Waiting => "Waiting",
+            FishingState::Hooked => "Hooked",
+            FishingState::Reeling => "Reeling",
+            FishingState::Recasting => "Recasting",
+            FishingState::Paused => "Paused",
+        };
+
+        write!(f, "{name}")
+    }
+}
+
+/// Seconds spent in the casting states before polling again.
+///
+/// The time spent in `Hooked` and `Reeling` is set by [`crate::timing::Timing`].
+#[derive(Debug, Clone, SmartDefault, Serialize, Deserialize)]
+#[serde(default)]
+pub struct StateTimeouts {
+    #[default(1.0)]
+    pub casting: f32,
+    #[default(1.0)]
+    pub recasting: f32,
+}
@@ -0,0 +1,64 @@
+use std::{collections::BTreeMap, time::Duration};
+
+use tokio::time::Instant;
+
+use crate::fishing::FishingEvt;
+
+/// Counters for the running session, fed by [`FishingEvt`]s.
+#[derive(Debug, Clone, Default)]
+pub struct SessionStats {
+    pub started_at: Option<Instant>,
+    pub stopped_at: Option<Instant>,
+    pub frames: u64,
+    pub ocr_calls: u64,
+    pub clicks: u64,
+    pub timeout_recasts: u32,
+    pub matches: BTreeMap<String, u32>,
+}
+
+impl SessionStats {
+    pub fn start() -> Self {
+        Self {
+            started_at: Some(Instant::now()),
+            ..Default::default()
+        }
+    }
+
+    /// Freezes the uptime so the panel still shows the finished session
+    pub fn stop(&mut self) {
+        self.stopped_at.get_or_insert_with(Instant::now);
+    }
+
+    pub fn apply(&mut self, evt: &FishingEvt) {
+        match evt {
+            FishingEvt::FrameCaptured => self.frames += 1,
+            FishingEvt::OcrDone => self.ocr_calls += 1,
+            FishingEvt::ClickSent(count) => self.clicks += u64::from(*count),
+            FishingEvt::TimeoutRecast => self.timeout_recasts += 1,
+            FishingEvt::Matched(keyword) => *self.matches.entry(keyword.clone()).or_default() += 1,
+            _ => {}
+        }
+    }
+
+    pub fn uptime(&self) -> Duration {
+        let end = self.stopped_at.unwrap_or_else(Instant::now);
+
+        self.started_at
+            .map(|start| end.duration_since(start))
+            .unwrap_or_default()
+    }
+
+    pub fn total_matches(&self) -> u32 {
+        self.matches.values().sum()
+    }
+
+    pub fn catches_per_hour(&self) -> f64 {
+        let hours = self.uptime().as_secs_f64() / 3600.0;
+
+        if hours > 0.0 {
+            f64::from(self.total_matches()) / hours
+        } else {
+            0.0
+        }
+    }
+}
@@ -0,0 +1,145 @@
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+use tokio::time::Instant;
+
+use crate::matcher::{Matcher, RuleErr};
+
+/// When a session should end on its own.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct StopConditions {
+    /// Stop after this many catches of `count_keyword`
+    pub target_count: Option<u32>,
+    /// The item counted towards `target_count`, any catch counts if unset
+    pub count_keyword: Option<String>,
+    /// Seconds after which the session stops
+    pub max_duration: Option<f32>,
+    /// Stop right after this item is caught
+    pub stop_on: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum StopReason {
+    TargetReached(u32),
+    MaxDuration(f32),
+    Caught(String),
+    Stopped,
+}
+
+impl fmt::Display for StopReason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            StopReason::TargetReached(count) => write!(f, "Reached the target of {count} catches"),
+            StopReason::MaxDuration(secs) => write!(f, "Ran for the maximum of {secs}s"),
+            StopReason::Caught(name) => write!(f, "Caught {name}"),
+            StopReason::Stopped => write!(f, "Stopped"),
+        }
+    }
+}
+
+/// Evaluates the [`StopConditions`] over one session.
+pub struct StopTracker {
+    conditions: StopConditions,
+    /// `count_keyword` and `stop_on` parsed like the keyword field, so OCR misreads count too
+    count_matcher: Option<Matcher>,
+    stop_matcher: Option<Matcher>,
+    started_at: Instant,
+    count: u32,
+}
+
+impl StopTracker {
+    pub fn new(conditions: StopConditions, default_distance: usize) -> Result<Self, RuleErr> {
+        let parse = |name: &Option<String>| {
+            name.as_deref()
+                .map(|name| Matcher::parse(name, default_distance))
+                .transpose()
+        };
+
+        Ok(Self {
+            count_matcher: parse(&conditions.count_keyword)?,
+            stop_matcher: parse(&conditions.stop_on)?,
+            conditions,
+            started_at: Instant::now(),
+            count: 0,
+        })
+    }
+
+    pub fn check_duration(&self) -> Option<StopReason> {
+        let max = self.conditions.max_duration?;
+        (self.started_at.elapsed().as_secs_f32() >= max).then_some(StopReason::MaxDuration(max))
+    }
+
+    /// Counts a catch matched by the rule `source` in the OCR `text`.
+    pub fn record_catch(&mut self, source: &str, text: &str) -> Option<StopReason> {
+        let counts = match (&self.conditions.count_keyword, &self.count_matcher) {
+            (Some(name), Some(matcher)) => mentions(name, matcher, source, text),
+            _ => true,
+        };
+
+        if counts {
+            self.count += 1;
+        }
+
+        if let (Some(name), Some(matcher)) = (&self.conditions.stop_on, &self.stop_matcher)
+            && mentions(name, matcher, source, text)
+        {
+            return Some(StopReason::Caught(name.clone()));
+        }
+
+        let target = self.conditions.target_count?;
+        (self.count >= target).then_some(StopReason::TargetReached(target))
+    }
+}
+
+/// Whether the catch is `name`, either by the rule that matched or by the text itself
+fn mentions(name: &str, matcher: &Matcher, source: &str, text: &str) -> bool {
+    rule_name(source).eq_ignore_ascii_case(rule_name(name)) || matcher.evaluate(text).is_hit()
+}
+
+/// The keyword of a fuzzy rule without its `~N` suffix
+fn rule_name(source: &str) -> &str {
+    match source.rsplit_once('~') {
+        Some((name, distance)) if distance.parse::<usize>().is_ok() => name.trim(),
+        _ => source.trim(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tracker(conditions: StopConditions) -> StopTracker {
+        StopTracker::new(conditions, 1).unwrap()
+    }
+
+    #[test]
+    fn stop_on_follows_the_matched_rule() {
+        let mut stop = tracker(StopConditions {
+            stop_on: Some("Golden Carp".into()),
+            ..Default::default()
+        });
+
+        assert_eq!(stop.record_catch("Ebonkoi", "Ebonkoi"), None);
+        assert_eq!(
+            stop.record_catch("Golden Carp~2", "G0lden Crap"),
+            Some(StopReason::Caught("Golden Carp".into()))
+        );
+    }
+
+    #[test]
+    fn count_keyword_tolerates_misreads() {
+        let mut stop = tracker(StopConditions {
+            target_count: Some(2),
+            count_keyword: Some("Golden Carp".into()),
+            ..Default::default()
+        });
+
+        assert_eq!(stop.record_catch("/carp/i", "G0lden Carp"), None);
+        assert_eq!(stop.record_catch("/crate/i", "Wooden Crate"), None);
+        assert_eq!(
+            stop.record_catch("/carp/i", "Golden Carq"),
+            Some(StopReason::TargetReached(2))
+        );
+    }
+}
@@ -0,0 +1,192 @@
+use rand::{Rng, SeedableRng, rngs::StdRng};
+use serde::{Deserialize, Serialize};
+use smart_default::SmartDefault;
+
+/// Seconds between a hit, the reel and the recast.
+#[derive(Debug, Clone, SmartDefault, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Timing {
+    /// From the keyword hit to the reel action
+    #[default(0.0)]
+    pub reel_delay: f32,
+    /// From the reel action to the recast action
+    #[default(1.0)]
+    pub recast_delay: f32,
+    /// Random `[min, max]` seconds added to the reel delay
+    pub reel_jitter: Option<(f32, f32)>,
+    /// Random `[min, max]` seconds added to the recast delay
+    pub recast_jitter: Option<(f32, f32)>,
+    /// Makes the jitter reproducible between runs
+    pub seed: Option<u64>,
+}
+
+impl Timing {
+    /// Rejects the delays and jitter ranges the humanizer cannot sample from.
+    ///
+    /// The jitter bounds may be negative, the sum is clamped at zero, but must be finite.
+    pub fn validate(&self) -> Result<(), String> {
+        for (name, delay) in [
+            ("reel_delay", self.reel_delay),
+            ("recast_delay", self.recast_delay),
+        ] {
+            if !valid_secs(delay) {
+                return Err(format!("Invalid {name}: {delay}"));
+            }
+        }
+
+        for (name, jitter) in [
+            ("reel_jitter", self.reel_jitter),
+            ("recast_jitter", self.recast_jitter),
+        ] {
+            if let Some((a, b)) = jitter
+                && !(a.is_finite() && b.is_finite())
+            {
+                return Err(format!("Invalid {name}: [{a}, {b}]"));
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Whether `secs` can be slept, i.e. finite and not negative
+pub fn valid_secs(secs: f32) -> bool {
+    secs.is_finite() && secs >= 0.0
+}
+
+/// Parses a delay in seconds, rejecting the values [`valid_secs`] refuses
+pub fn parse_secs(raw: &str) -> Result<f32, String> {
+    match raw.trim().parse::<f32>() {
+        Ok(secs) if valid_secs(secs) => Ok(secs),
+        Ok(_) => Err(format!(
+            "{raw} is not a finite, non-negative number of seconds"
+        )),
+        Err(_) => Err(format!("{raw} is not a number")),
+    }
+}
+
+/// Adds the configured jitter to the reel and recast delays.
+pub struct Humanizer {
+    timing: Timing,
+    rng: StdRng,
+}
+
+impl Humanizer {
+    pub fn new(timing: Timing) -> Self {
+        let rng = match timing.seed {
+            Some(seed) => StdRng::seed_from_u64(seed),
+            None => StdRng::from_entropy(),
+        };
+
+        Self { timing, rng }
+    }
+
+    pub fn reel_delay(&mut self) -> f32 {
+        self.jittered(self.timing.reel_delay, self.timing.reel_jitter)
+    }
+
+    pub fn recast_delay(&mut self) -> f32 {
+        self.jittered(self.timing.recast_delay, self.timing.recast_jitter)
+    }
+
+    fn jittered(&mut self, base: f32, jitter: Option<(f32, f32)>) -> f32 {
+        let offset = match jitter {
+            Some((a, b)) => self.rng.gen_range(a.min(b)..=a.max(b)),
+            None => 0.0,
+        };
+
+        (base + offset).max(0.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn timing(seed: u64) -> Timing {
+        Timing {
+            reel_delay: 0.2,
+            recast_delay: 1.0,
+            reel_jitter: Some((-0.5, 0.3)),
+            recast_jitter: Some((0.5, 0.1)),
+            seed: Some(seed),
+        }
+    }
+
+    /// Slack for the rounding of `base + offset`
+    const EPSILON: f32 = 1e-6;
+
+    #[test]
+    fn delays_stay_within_the_jitter() {
+        let mut humanizer = Humanizer::new(timing(7));
+
+        for _ in 0..1000 {
+            let reel = humanizer.reel_delay();
+            assert!((0.0..=0.5 + EPSILON).contains(&reel), "reel delay {reel}");
+
+            let recast = humanizer.recast_delay();
+            assert!(
+                (1.1 - EPSILON..=1.5 + EPSILON).contains(&recast),
+                "recast delay {recast}"
+            );
+        }
+    }
+
+    #[test]
+    fn no_jitter_keeps_the_base() {
+        let mut humanizer = Humanizer::new(Timing::default());
+
+        assert_eq!(humanizer.reel_delay(), 0.0);
+        assert_eq!(humanizer.recast_delay(), 1.0);
+    }
+
+    #[test]
+    fn same_seed_same_sequence() {
+        let mut a = Humanizer::new(timing(42));
+        let mut b = Humanizer::new(timing(42));
+
+        for _ in 0..100 {
+            assert_eq!(a.reel_delay(), b.reel_delay());
+            assert_eq!(a.recast_delay(), b.recast_delay());
+        }
+    }
+
+    #[test]
+    fn validate_rejects_non_finite_jitter() {
+        assert_eq!(timing(1).validate(), Ok(()));
+
+        for jitter in [
+            (0.0, f32::INFINITY),
+            (f32::NEG_INFINITY, 0.0),
+            (f32::NAN, 1.0),
+        ] {
+            let reel = Timing {
+                reel_jitter: Some(jitter),
+                ..timing(1)
+            };
+            assert!(reel.validate().is_err(), "{jitter:?} should be rejected");
+
+            let recast = Timing {
+                recast_jitter: Some(jitter),
+                ..timing(1)
+            };
+            assert!(recast.validate().is_err(), "{jitter:?} should be rejected");
+        }
+
+        let negative = Timing {
+            recast_delay: -1.0,
+            ..timing(1)
+        };
+        assert!(negative.validate().is_err());
+    }
+
+    #[test]
+    fn parse_secs_rejects_unsleepable_values() {
+        assert_eq!(parse_secs(" 0.5 "), Ok(0.5));
+        assert_eq!(parse_secs("0"), Ok(0.0));
+
+        for raw in ["-1", "NaN", "inf", "-inf", "abc", ""] {
+            assert!(parse_secs(raw).is_err(), "{raw} should not parse");
+        }
+    }
+}
@@ -3,6 +3,8 @@ pub enum TrayEvents {
     Open,
     Toggle,
     Quit,
+    /// Switch to the profile after the active one
+    NextProfile,
     PassSender(tokio::sync::mpsc::Sender<TrayInput>),
     Err(String),
 }
@@ -12,6 +14,8 @@ pub enum TrayInput {
     Started,
     Stopped,
     IconUpdate(String),
+    /// The active profile changed or a profile was saved
+    ActiveProfile(Option<String>),
 }
 
 use iced::futures::{SinkExt, channel::mpsc::Sender};
@@ -51,6 +55,28 @@ pub async fn create_icon(mut tx: Sender<TrayEvents>) -> Result<(), Box<dyn std::
             });
     })?;
 
+    // tray-item can neither nest menus nor remove items, so instead of listing the profiles one
+    // item cycles through them and its label shows the active one
+    let profile_tx = internal_tx_clone.clone();
+    let next_profile = move || {
+        profile_tx
+            .blocking_send(TrayEvents::NextProfile)
+            .unwrap_or_else(|e| {
+                println!("Failed to send: {e}");
+            });
+    };
+
+    #[cfg(target_os = "linux")]
+    let profile_item = tray
+        .inner_mut()
+        .add_menu_item_with_id(&profile_label(None), next_profile)?;
+
+    #[cfg(not(target_os = "linux"))]
+    let profile_item = {
+        tray.add_menu_item("Next profile", next_profile)?;
+        0
+    };
+
     // Add a quit option
     tray.add_menu_item("Quit", move || {
         println!("Exiting application");
@@ -80,7 +106,7 @@ pub async fn create_icon(mut tx: Sender<TrayEvents>) -> Result<(), Box<dyn std::
             }
 
             Some(evt) = input_rx.recv() => {
-                process_evt(evt, &mut tray);
+                process_evt(evt, &mut tray, profile_item);
             }
         }
     }
@@ -88,11 +114,24 @@ pub async fn create_icon(mut tx: Sender<TrayEvents>) -> Result<(), Box<dyn std::
     Ok(())
 }
 
-fn process_evt(evt: TrayInput, tray: &mut TrayItem) {
+fn profile_label(active: Option<&str>) -> String {
+    format!("Profile: {}", active.unwrap_or("none"))
+}
+
+fn process_evt(evt: TrayInput, tray: &mut TrayItem, profile_item: u32) {
     let res = match evt {
         TrayInput::Started => tray.set_icon(IconSource::Resource("emblem-pause")),
         TrayInput::Stopped => tray.set_icon(IconSource::Resource("checkmark")),
         TrayInput::IconUpdate(_) => Ok(()),
+        #[cfg(target_os = "linux")]
+        TrayInput::ActiveProfile(name) => tray
+            .inner_mut()
+            .set_menu_item_label(&profile_label(name.as_deref()), profile_item),
+        #[cfg(not(target_os = "linux"))]
+        TrayInput::ActiveProfile(_) => {
+            let _ = profile_item;
+            Ok(())
+        }
     };
 
     if res.is_err() {
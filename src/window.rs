@@ -1,6 +1,6 @@
 use iced::{
     Alignment, Element, Length, Theme,
-    widget::{button, column, container, row, text, text_input},
+    widget::{Column, button, column, container, pick_list, row, text, text_input},
 };
 use smart_default::SmartDefault;
 
@@ -22,20 +22,139 @@ impl Window {
 
         let select_range_button = button("Select range").on_press(Message::GetScale);
 
-        let scale_text = text(format!("{}", context.args.scale));
+        let region_text = match (&context.region_err, &context.args.region) {
+            (Some(e), _) => text(e.to_string()).style(|theme: &Theme| text::Style {
+                color: Some(theme.palette().danger),
+            }),
+            (None, Some(region)) => text(region.to_string()),
+            (None, None) => text("No region selected"),
+        };
 
         let time_input = text_input("0.5", &context.raw_time)
             .on_input(Message::TimeInterval)
+            .on_submit(Message::CommitFields)
             .padding(10);
 
         let name_input = text_input("ebonkoi", &context.args.keyword)
             .on_input(Message::ItemName)
+            .on_submit(Message::CommitFields)
+            .padding(10);
+
+        let reel_input = text_input("left@20", &context.raw_reel)
+            .on_input(Message::ReelAction)
+            .on_submit(Message::CommitFields)
+            .padding(10);
+
+        let recast_input = text_input("left@20", &context.raw_recast)
+            .on_input(Message::RecastAction)
+            .on_submit(Message::CommitFields)
+            .padding(10);
+
+        let action_errs = [("Reel", &context.reel_err), ("Recast", &context.recast_err)]
+            .into_iter()
+            .filter_map(|(name, err)| err.as_ref().map(|e| format!("{name}: {e}")));
+
+        let action_err = Column::with_children(action_errs.map(|line| {
+            text(line)
+                .size(12)
+                .style(|theme: &Theme| text::Style {
+                    color: Some(theme.palette().danger),
+                })
+                .into()
+        }));
+
+        let profile_names: Vec<String> = context.profiles.iter().map(|p| p.name.clone()).collect();
+
+        let profile_picker = pick_list(
+            profile_names,
+            context.active_profile.clone(),
+            Message::SelectProfile,
+        )
+        .placeholder("Profile");
+
+        let profile_input = text_input("Profile name", &context.profile_name)
+            .on_input(Message::ProfileName)
             .padding(10);
 
+        let save_profile_button = button("Save profile").on_press(Message::SaveProfile);
+
+        let check_setup_button = button("Check setup").on_press(Message::CheckSetup);
+        let export_button = button("Export CSV").on_press(Message::ExportCsv);
+
+        let setup_report = Column::with_children(context.setup_report.iter().map(|check| {
+            let line = if check.ok {
+                format!("✔ {}: {}", check.name, check.detail)
+            } else {
+                format!("✘ {}: {}. {}", check.name, check.detail, check.hint)
+            };
+
+            let ok = check.ok;
+            text(line)
+                .size(14)
+                .style(move |theme: &Theme| text::Style {
+                    color: Some(if ok {
+                        theme.palette().success
+                    } else {
+                        theme.palette().danger
+                    }),
+                })
+                .into()
+        }))
+        .spacing(4);
+
+        let stats = &context.stats;
+        let stats_panel = match stats.started_at {
+            Some(_) => {
+                let uptime = stats.uptime().as_secs();
+                let mut lines = vec![
+                    format!(
+                        "Uptime: {:02}:{:02}:{:02}",
+                        uptime / 3600,
+                        uptime / 60 % 60,
+                        uptime % 60
+                    ),
+                    format!(
+                        "Frames: {}  OCR calls: {}  Clicks: {}  Timeout recasts: {}",
+                        stats.frames, stats.ocr_calls, stats.clicks, stats.timeout_recasts
+                    ),
+                    format!(
+                        "Catches: {} ({:.1}/h)",
+                        stats.total_matches(),
+                        stats.catches_per_hour()
+                    ),
+                ];
+
+                lines.extend(
+                    stats
+                        .matches
+                        .iter()
+                        .map(|(keyword, count)| format!("  {keyword}: {count}")),
+                );
+
+                Column::with_children(lines.into_iter().map(|line| text(line).size(14).into()))
+                    .spacing(4)
+            }
+            None => Column::new(),
+        };
+
         // Handle button style based on state
         let action_button = match &context.handle {
+            Some(_) if context.is_stopping => button("Force stop").on_press(Message::Stop),
             Some(_) => button("Stop").on_press(Message::Stop),
-            None => button("Start").on_press(Message::Start),
+            None => button("Start").on_press_maybe(
+                (context.args.region.is_some()
+                    && context.reel_err.is_none()
+                    && context.recast_err.is_none())
+                .then_some(Message::Start),
+            ),
+        };
+
+        let pause_button = match (&context.control, context.is_paused) {
+            (Some(_), true) => button("Resume").on_press(Message::Resume),
+            (Some(_), false) => {
+                button("Pause").on_press_maybe((!context.is_stopping).then_some(Message::Pause))
+            }
+            (None, _) => button("Pause"),
         };
 
         // Layout with spacing and padding
@@ -43,7 +162,15 @@ impl Window {
             column![
                 title,
                 row![
-                    column![select_range_button, scale_text]
+                    profile_picker,
+                    profile_input.width(Length::Fill),
+                    save_profile_button,
+                ]
+                .spacing(20)
+                .padding([0, 20])
+                .align_y(Alignment::Center),
+                row![
+                    column![select_range_button, region_text]
                         .spacing(10)
                         .width(Length::Fill),
                     text("Interval:"),
@@ -53,7 +180,29 @@ impl Window {
                 ]
                 .spacing(20)
                 .padding(20),
-                action_button,
+                row![
+                    text("Reel:"),
+                    reel_input.width(Length::Fill),
+                    text("Recast:"),
+                    recast_input.width(Length::Fill),
+                ]
+                .spacing(20)
+                .padding([0, 20])
+                .align_y(Alignment::Center),
+                text(
+                    "Actions: left, right, middle or key:CODE, then optional @HOLD_MS and xREPEAT"
+                )
+                .size(12),
+                action_err,
+                row![
+                    action_button,
+                    pause_button,
+                    check_setup_button,
+                    export_button
+                ]
+                .spacing(20),
+                setup_report,
+                text(context.export_status.clone()).size(14),
                 text(context.err.clone()).style(|theme: &Theme| {
                     text::Style {
                         color: Some(theme.palette().danger),
@@ -67,7 +216,15 @@ impl Window {
                     } else {
                         text(context.count_down.to_string()).size(50)
                     }
-                }
+                },
+                text(
+                    context
+                        .state
+                        .map(|state| format!("State: {state}"))
+                        .unwrap_or_default()
+                ),
+                text(context.finish_reason.clone()),
+                stats_panel,
             ]
             .spacing(20)
             .padding(20)